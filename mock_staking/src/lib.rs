@@ -0,0 +1,76 @@
+/*!
+* Mock staking contract for the session vault tests
+*
+* Takes stakes by ft_transfer_call, msg being the beneficiary, and sends them back
+* on `unstake` with `reward_percent` of rewards, paid out of its own token balance.
+*/
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{env, near, AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue};
+
+const GAS_FOR_UNSTAKE_TRANSFER: Gas = Gas::from_gas(50_000_000_000_000);
+
+#[near(serializers=[borsh], contract_state)]
+#[derive(PanicOnDefault)]
+pub struct Contract {
+    token_id: AccountId,
+    reward_percent: u8,
+    stakes: LookupMap<AccountId, u128>,
+}
+
+#[near]
+impl Contract {
+    #[init]
+    pub fn new(token_id: AccountId, reward_percent: u8) -> Self {
+        Contract {
+            token_id,
+            reward_percent,
+            stakes: LookupMap::new(b"s".to_vec()),
+        }
+    }
+
+    /// Sends `amount` staked for `account_id` back to the vault that staked it, with
+    /// its rewards, msg being `UNSTAKE:<account_id>`.
+    pub fn unstake(&mut self, account_id: AccountId, amount: U128) -> Promise {
+        let staked = self.stakes.get(&account_id).unwrap_or(0);
+        assert!(amount.0 <= staked, "ERR_NOT_ENOUGH_STAKED");
+        self.stakes.insert(&account_id, &(staked - amount.0));
+        let rewards = amount.0 * self.reward_percent as u128 / 100;
+        ext_ft_core::ext(self.token_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_UNSTAKE_TRANSFER)
+            .ft_transfer_call(
+                env::predecessor_account_id(),
+                U128(amount.0 + rewards),
+                None,
+                format!("UNSTAKE:{}", account_id),
+            )
+    }
+
+    pub fn get_staked(&self, account_id: AccountId) -> U128 {
+        U128(self.stakes.get(&account_id).unwrap_or(0))
+    }
+}
+
+#[near]
+impl FungibleTokenReceiver for Contract {
+    fn ft_on_transfer(
+        &mut self,
+        _sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.token_id,
+            "ERR_ILLEGAL_TOKEN"
+        );
+        if let Ok(account_id) = msg.parse::<AccountId>() {
+            let staked = self.stakes.get(&account_id).unwrap_or(0);
+            self.stakes.insert(&account_id, &(staked + amount.0));
+        }
+        PromiseOrValue::Value(U128(0))
+    }
+}
@@ -0,0 +1,104 @@
+//! Acceleration clauses, eg. on an acquisition: part or all of the remaining
+//! sessions of some or all accounts unlock at once.
+//!
+//! The accelerated sessions become claimable immediately and the rest of the
+//! schedule moves earlier by as many sessions, so the end of vesting comes sooner.
+use crate::account::Account;
+use crate::events::emit_event;
+use crate::milestone::MilestoneStatus;
+use crate::utils::*;
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde_json::json;
+
+impl Account {
+    /// Unlock `fraction_bps` of the remaining sessions (or locked milestones) at `cur_ts`,
+    /// rounded down. Returns how many were unlocked.
+    fn accelerate(&mut self, fraction_bps: u32, cur_ts: u64) -> u32 {
+        if self.termination.is_some() {
+            return 0;
+        }
+        if !self.milestones.is_empty() {
+            let locked = self
+                .milestones
+                .iter()
+                .filter(|m| m.status == MilestoneStatus::Locked)
+                .count() as u64;
+            let accelerated = (locked * fraction_bps as u64 / BPS_DENOMINATOR as u64) as u32;
+            self.milestones
+                .iter_mut()
+                .filter(|m| m.status == MilestoneStatus::Locked)
+                .take(accelerated as usize)
+                .for_each(|m| m.status = MilestoneStatus::Released);
+            self.accelerated_sessions += accelerated;
+            return accelerated;
+        }
+        let remaining = self.session_num - self.unlocked_sessions(cur_ts).min(self.session_num);
+        let accelerated = (remaining as u64 * fraction_bps as u64 / BPS_DENOMINATOR as u64) as u32;
+        self.accelerated_sessions += accelerated;
+        accelerated
+    }
+}
+
+#[near]
+impl Contract {
+    /// Unlock `fraction_bps` (in basis points) of the remaining sessions of the given
+    /// accounts, or with None of the page of `limit` accounts from `from_index`.
+    /// Terminated accounts are left untouched. Returns how many accounts were accelerated.
+    /// Only can be called by owner, which can be a timelock contract.
+    #[payable]
+    pub fn accelerate(
+        &mut self,
+        account_ids: Option<Vec<AccountId>>,
+        fraction_bps: u32,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> u32 {
+        self.assert_owner();
+        assert_one_yocto();
+        assert!(
+            fraction_bps > 0 && fraction_bps <= BPS_DENOMINATOR,
+            "ERR_INVALID_FRACTION"
+        );
+        let limit = limit.unwrap_or(MAX_ACCELERATE_LIMIT);
+        assert!(
+            limit > 0 && limit <= MAX_ACCELERATE_LIMIT,
+            "ERR_INVALID_LIMIT"
+        );
+        let account_ids = account_ids.unwrap_or_else(|| {
+            self.data()
+                .accounts
+                .keys()
+                .skip(from_index.unwrap_or(U64(0)).0 as usize)
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        });
+        assert!(account_ids.len() <= limit as usize, "ERR_TOO_MANY_ACCOUNTS");
+        let cur_ts = env::block_timestamp();
+        let mut accelerated_accounts = 0;
+        for account_id in account_ids {
+            let mut account = self
+                .data()
+                .accounts
+                .get(&account_id)
+                .map(|va| va.clone().into_current())
+                .expect("ERR_ACCOUNT_NOT_EXIST");
+            let accelerated = account.accelerate(fraction_bps, cur_ts);
+            if accelerated == 0 {
+                continue;
+            }
+            emit_event(
+                "account_accelerated",
+                json!({
+                    "account_id": account_id,
+                    "accelerated_sessions": accelerated,
+                    "total_accelerated_sessions": account.accelerated_sessions,
+                }),
+            );
+            self.data_mut().accounts.insert(account_id, account.into());
+            accelerated_accounts += 1;
+        }
+        accelerated_accounts
+    }
+}
@@ -1,17 +1,25 @@
 use std::str::FromStr;
 
 // use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
-use crate::utils::ext_fungible_token;
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 
+use crate::freeze::assert_not_frozen;
+use crate::legacy::AccountV0;
+use crate::milestone::{emit_milestone_event, Milestone, MilestoneStatus};
+use crate::staking::assert_nothing_staked;
+use crate::termination::{Termination, TerminationPolicy};
 use crate::utils::*;
 use crate::*;
-use near_sdk::{env, is_promise_success, log, near, AccountId, NearToken, PromiseOrValue};
+use near_sdk::{
+    env, is_promise_success, log, near, AccountId, NearToken, PromiseOrValue, PublicKey,
+};
 
 // #[derive(BorshDeserialize, BorshSerialize)]
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone)]
 #[near(serializers = [borsh, json])]
 pub enum VAccount {
+    V0(AccountV0),
     Current(Account),
 }
 
@@ -19,11 +27,141 @@ impl VAccount {
     /// Upgrades from other versions to the currently used version.
     pub fn into_current(self) -> Account {
         match self {
+            VAccount::V0(account) => account.into(),
             VAccount::Current(account) => account,
         }
     }
 }
 
+impl From<AccountV0> for Account {
+    fn from(account: AccountV0) -> Self {
+        Account {
+            account_id: account.account_id,
+            start_timestamp: account.start_timestamp,
+            session_interval: account.session_interval,
+            session_num: account.session_num,
+            last_claim_session: account.last_claim_session,
+            release_per_session: account.release_per_session,
+            claimed_amount: account.claimed_amount,
+            deposited_amount: account.deposited_amount,
+            claim_policy: ClaimPolicy::Anyone,
+            claim_public_key: None,
+            claim_nonce: U64(0),
+            pending_amount: 0.into(),
+            last_claim_error: None,
+            in_flight_amount: 0.into(),
+            transfer_to: None,
+            transfer_approved: false,
+            interval_unit: IntervalUnit::Second,
+            remainder_amount: 0.into(),
+            remainder_policy: RemainderPolicy::LastSession,
+            milestones: vec![],
+            termination_policy: TerminationPolicy::default(),
+            termination: None,
+            accelerated_sessions: 0,
+            claim_deadline: None,
+            expired: false,
+            funder_id: None,
+            token_id: None,
+            native: false,
+            tge_amount: 0.into(),
+            cliff: U64(0),
+            category: None,
+            nft_token_id: None,
+            nft_transfer_disabled: false,
+            staking_id: None,
+            staked_amount: 0.into(),
+            staking_rewards: 0.into(),
+            freeze: None,
+        }
+    }
+}
+
+/// How the part of a total amount not divisible by session_num is released.
+#[derive(Clone, Copy, Default)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum RemainderPolicy {
+    // released together with the final session
+    #[default]
+    LastSession,
+    // spread over the sessions, at most 1 more per session
+    Spread,
+}
+
+/// What `session_interval` is counted in.
+#[derive(Clone, Copy, Default)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum IntervalUnit {
+    // fixed number of seconds, session n unlocks at start + n * interval
+    #[default]
+    Second,
+    // UTC calendar months, session n unlocks on the same day of month as start,
+    // n * interval months later (clamped to the month end), at the same time of day
+    Month,
+}
+
+/// Optional settings of a grant given to `add_account`, omitted fields use their default.
+#[derive(Clone, Default)]
+#[near(serializers = [json])]
+pub struct AccountOptions {
+    #[serde(default)]
+    pub interval_unit: IntervalUnit,
+    // total amount of the round, instead of release_per_session
+    #[serde(default)]
+    pub total_amount: Option<U128>,
+    #[serde(default)]
+    pub remainder_policy: RemainderPolicy,
+    #[serde(default)]
+    pub termination_policy: TerminationPolicy,
+    // overrides the claim deadline of the vault
+    #[serde(default)]
+    pub claim_deadline: Option<TimestampSec>,
+    // a whitelisted token, instead of the default one
+    #[serde(default)]
+    pub token_id: Option<AccountId>,
+    // in native NEAR, funded with fund_account_native
+    #[serde(default)]
+    pub native: bool,
+    // released at start by an extra first session, part of total_amount if given
+    #[serde(default)]
+    pub tge_amount: U128,
+    // in interval_unit, sessions due before start + cliff unlock at its end
+    #[serde(default)]
+    pub cliff: TimestampSec,
+    // tokenomics category counted against its cap, in the default token only
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Who is allowed to trigger a claim on behalf of a beneficiary.
+/// The beneficiary itself can always claim.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum ClaimPolicy {
+    // only the beneficiary can claim
+    SelfOnly,
+    // any account can claim for the beneficiary
+    Anyone,
+    // the beneficiary and the listed delegates can claim
+    Delegates(Vec<AccountId>),
+}
+
+impl ClaimPolicy {
+    pub fn allows(&self, account_id: &AccountId, claimer_id: &AccountId) -> bool {
+        if account_id == claimer_id {
+            return true;
+        }
+        match self {
+            ClaimPolicy::SelfOnly => false,
+            ClaimPolicy::Anyone => true,
+            ClaimPolicy::Delegates(delegates) => delegates.contains(claimer_id),
+        }
+    }
+}
+
 impl From<Account> for VAccount {
     fn from(account: Account) -> Self {
         VAccount::Current(account)
@@ -50,7 +188,7 @@ pub struct Account {
     pub session_num: u32,
     // the session index of previous claim, start from 1
     pub last_claim_session: u32,
-    // expected total amount this time = session_num * release_per_session
+    // expected total amount this time = session_num * release_per_session + remainder_amount
     pub release_per_session: U128,
 
     // accumulated claimed amount since account created,
@@ -59,36 +197,307 @@ pub struct Account {
     // accumulated deposited amount since account created,
     // each time ft_transfer_call would increase this one
     pub deposited_amount: U128,
+
+    // who may trigger a claim for this account, managed by the beneficiary
+    pub claim_policy: ClaimPolicy,
+    // ed25519 key allowed to sign claims relayed by others
+    pub claim_public_key: Option<PublicKey>,
+    // last nonce used by a signed claim
+    pub claim_nonce: U64,
+
+    // claimed amount whose transfer failed, waiting for withdraw_pending
+    pub pending_amount: U128,
+    // why the last claim transfer failed, cleared by a successful one
+    pub last_claim_error: Option<String>,
+    // sent by a claim or withdrawal whose transfer is not resolved yet
+    pub in_flight_amount: U128,
+
+    // new account proposed by the beneficiary to take over this grant
+    pub transfer_to: Option<AccountId>,
+    // owner approved the proposed transfer
+    pub transfer_approved: bool,
+
+    // unit of session_interval
+    pub interval_unit: IntervalUnit,
+
+    // part of the total not divisible by session_num, always less than session_num
+    pub remainder_amount: U128,
+    // how remainder_amount is released
+    pub remainder_policy: RemainderPolicy,
+
+    // when not empty, each milestone is a session released by an attester instead of time
+    pub milestones: Vec<Milestone>,
+
+    // what the account keeps when terminated, chosen by owner at add_account
+    pub termination_policy: TerminationPolicy,
+    // set once the account was terminated
+    pub termination: Option<Termination>,
+
+    // sessions (or milestones) unlocked ahead of time by accelerate,
+    // the rest of a time based schedule moves earlier
+    pub accelerated_sessions: u32,
+
+    // no claim after it, overrides the one of the vault
+    pub claim_deadline: Option<TimestampSec>,
+    // swept after its claim deadline
+    pub expired: bool,
+
+    // who deposited the current round, if the vault records funders
+    pub funder_id: Option<AccountId>,
+
+    // token of the grant, None for the default one
+    pub token_id: Option<AccountId>,
+
+    // in native NEAR instead of a token
+    pub native: bool,
+
+    // when not zero, released by the first session at start, the others vest after it
+    pub tge_amount: U128,
+    // in interval_unit, vesting sessions due before start + cliff unlock at its end
+    pub cliff: TimestampSec,
+
+    // tokenomics category of the grant
+    pub category: Option<String>,
+
+    // NEP-171 token representing the grant, moves with it
+    pub nft_token_id: Option<String>,
+    // owner disabled nft_transfer of the grant
+    pub nft_transfer_disabled: bool,
+
+    // contract part of the locked balance is staked in
+    pub staking_id: Option<AccountId>,
+    // locked but out of the vault, staked in staking_id
+    pub staked_amount: U128,
+    // earned by staking, waiting for withdraw_staking_rewards
+    pub staking_rewards: U128,
+
+    // compliance hold, no claim, transfer or claim policy change while set
+    pub freeze: Option<Freeze>,
 }
 
 impl Account {
-    pub(crate) fn unclaimed_amount(&self, cur_ts: u64) -> u128 {
-        if self.last_claim_session >= self.session_num {
-            return 0_u128;
+    pub(crate) fn new(
+        account_id: AccountId,
+        start_timestamp: TimestampSec,
+        session_interval: TimestampSec,
+        session_num: u32,
+        release_per_session: U128,
+    ) -> Self {
+        Account {
+            account_id,
+            start_timestamp,
+            session_interval,
+            session_num,
+            last_claim_session: 0,
+            release_per_session,
+            claimed_amount: 0.into(),
+            deposited_amount: 0.into(),
+            claim_policy: ClaimPolicy::Anyone,
+            claim_public_key: None,
+            claim_nonce: U64(0),
+            pending_amount: 0.into(),
+            last_claim_error: None,
+            in_flight_amount: 0.into(),
+            transfer_to: None,
+            transfer_approved: false,
+            interval_unit: IntervalUnit::Second,
+            remainder_amount: 0.into(),
+            remainder_policy: RemainderPolicy::LastSession,
+            milestones: vec![],
+            termination_policy: TerminationPolicy::default(),
+            termination: None,
+            accelerated_sessions: 0,
+            claim_deadline: None,
+            expired: false,
+            funder_id: None,
+            token_id: None,
+            native: false,
+            tge_amount: 0.into(),
+            cliff: U64(0),
+            category: None,
+            nft_token_id: None,
+            nft_transfer_disabled: false,
+            staking_id: None,
+            staked_amount: 0.into(),
+            staking_rewards: 0.into(),
+            freeze: None,
+        }
+    }
+
+    fn milestones_amount(&self, status: &[MilestoneStatus]) -> u128 {
+        self.milestones
+            .iter()
+            .filter(|m| status.contains(&m.status))
+            .map(|m| m.amount.0)
+            .sum()
+    }
+
+    /// 1 if the round starts with a TGE session, else 0.
+    fn tge_sessions(&self) -> u32 {
+        (self.tge_amount.0 > 0) as u32
+    }
+
+    /// Sessions of the round besides the TGE one.
+    pub(crate) fn vesting_sessions(&self) -> u32 {
+        self.session_num - self.tge_sessions()
+    }
+
+    /// Timestamp (in seconds) `offset` units of interval_unit after start.
+    fn offset_timestamp(&self, offset: u64) -> u64 {
+        match self.interval_unit {
+            IntervalUnit::Second => self.start_timestamp.0 + offset,
+            IntervalUnit::Month => add_months(self.start_timestamp.0, offset),
         }
+    }
 
-        let cur_session = if cur_ts > to_nano(self.start_timestamp) {
-            ((cur_ts - to_nano(self.start_timestamp)) / to_nano(self.session_interval)) as u32
+    /// Expected total amount of the current round.
+    pub(crate) fn total_amount(&self) -> u128 {
+        if !self.milestones.is_empty() {
+            return self.milestones_amount(&[
+                MilestoneStatus::Locked,
+                MilestoneStatus::Released,
+                MilestoneStatus::Claimed,
+            ]);
+        }
+        let forfeited_amount = self
+            .termination
+            .as_ref()
+            .map(|termination| termination.forfeited_amount.0)
+            .unwrap_or(0);
+        self.tge_amount.0
+            + self.vesting_sessions() as u128 * self.release_per_session.0
+            + self.remainder_amount.0
+            - forfeited_amount
+    }
+
+    /// Amount released by the first `sessions` sessions of the round, exact at session_num.
+    pub(crate) fn released_amount(&self, sessions: u32) -> u128 {
+        let (tge_amount, sessions) = if sessions > 0 && self.tge_sessions() > 0 {
+            (self.tge_amount.0, sessions - 1)
         } else {
-            0
+            (0, sessions)
+        };
+        let vesting_sessions = self.vesting_sessions();
+        let remainder = match self.remainder_policy {
+            RemainderPolicy::LastSession if sessions >= vesting_sessions => self.remainder_amount.0,
+            RemainderPolicy::LastSession => 0,
+            RemainderPolicy::Spread => {
+                self.remainder_amount.0 * sessions as u128 / vesting_sessions as u128
+            }
         };
+        tge_amount + self.release_per_session.0 * sessions as u128 + remainder
+    }
 
-        let times = if cur_session >= self.session_num {
-            self.session_num - self.last_claim_session
-        } else {
-            cur_session - self.last_claim_session
+    /// Timestamp (in seconds) at which `session` is unlocked, session 0 being the start.
+    pub(crate) fn session_timestamp(&self, session: u32) -> u64 {
+        let session = session
+            .saturating_sub(self.accelerated_sessions)
+            .saturating_sub(self.tge_sessions());
+        if session == 0 {
+            return self.start_timestamp.0;
+        }
+        self.offset_timestamp(session as u64 * self.session_interval.0)
+            .max(self.offset_timestamp(self.cliff.0))
+    }
+
+    /// Number of sessions unlocked at `cur_ts` (in nanoseconds), may exceed session_num.
+    pub(crate) fn unlocked_sessions(&self, cur_ts: u64) -> u32 {
+        if !self.milestones.is_empty() {
+            return self
+                .milestones
+                .iter()
+                .filter(|m| {
+                    matches!(
+                        m.status,
+                        MilestoneStatus::Released | MilestoneStatus::Claimed
+                    )
+                })
+                .count() as u32;
+        }
+        if let Some(termination) = &self.termination {
+            return termination.kept_sessions;
+        }
+        if cur_ts <= to_nano(self.start_timestamp) {
+            return self.accelerated_sessions;
+        }
+        if cur_ts < to_nano(U64(self.offset_timestamp(self.cliff.0))) {
+            return self
+                .tge_sessions()
+                .saturating_add(self.accelerated_sessions);
+        }
+        let sessions = match self.interval_unit {
+            IntervalUnit::Second => {
+                ((cur_ts - to_nano(self.start_timestamp)) / to_nano(self.session_interval)) as u32
+            }
+            IntervalUnit::Month => {
+                (months_between(self.start_timestamp.0, cur_ts / 10u64.pow(9))
+                    / self.session_interval.0) as u32
+            }
         };
+        sessions
+            .saturating_add(self.tge_sessions())
+            .saturating_add(self.accelerated_sessions)
+    }
 
-        self.release_per_session.0 * times as u128
+    /// Number of unlocked sessions not claimed yet.
+    pub(crate) fn unclaimed_sessions(&self, cur_ts: u64) -> u32 {
+        if self.expired {
+            return 0;
+        }
+        self.unlocked_sessions(cur_ts)
+            .min(self.session_num)
+            .saturating_sub(self.last_claim_session)
+    }
+
+    pub(crate) fn unclaimed_amount(&self, cur_ts: u64) -> u128 {
+        let sessions = self.unclaimed_sessions(cur_ts);
+        if sessions == 0 {
+            return 0_u128;
+        }
+        if !self.milestones.is_empty() {
+            return self.milestones_amount(&[MilestoneStatus::Released]);
+        }
+        self.released_amount(self.last_claim_session + sessions)
+            - self.released_amount(self.last_claim_session)
+    }
+
+    /// Mark everything unclaimed at `cur_ts` as claimed.
+    pub(crate) fn record_claim(&mut self, cur_ts: u64) {
+        self.last_claim_session += self.unclaimed_sessions(cur_ts);
+        for milestone in self.milestones.iter_mut() {
+            if milestone.status == MilestoneStatus::Released {
+                milestone.status = MilestoneStatus::Claimed;
+            }
+        }
     }
 
     pub fn locking_amount(&self) -> U128 {
         U128::from(self.deposited_amount.0 - self.claimed_amount.0)
     }
+
+    /// Part of the locking amount that is in the vault, not staked out of it.
+    pub(crate) fn unstaked_amount(&self) -> u128 {
+        self.locking_amount().0 - self.staked_amount.0
+    }
+}
+
+pub(crate) fn assert_nothing_in_flight(account: &Account) {
+    assert!(account.in_flight_amount.0 == 0, "ERR_CLAIM_IN_FLIGHT");
 }
 
 impl Contract {
-    pub fn internal_deposit_to_account(&mut self, account_id: &AccountId, amount: U128) {
+    /// Owner and funders can fund accounts.
+    pub(crate) fn internal_is_funder(&self, sender_id: &AccountId) -> bool {
+        sender_id == &self.data().owner_id || self.data().funders.contains(sender_id)
+    }
+
+    pub fn internal_deposit_to_account(
+        &mut self,
+        account_id: &AccountId,
+        asset: &Asset,
+        amount: U128,
+        funder_id: Option<AccountId>,
+    ) {
         let mut account = self
             .data()
             .accounts
@@ -96,22 +505,64 @@ impl Contract {
             .map(|va| va.clone().into_current())
             .expect("ERR_ACCOUNT_NOT_EXIST");
         assert!(
-            account.locking_amount().0 == 0 && account.last_claim_session != account.session_num,
+            &self.internal_account_asset(&account) == asset,
+            "ERR_ILLEGAL_TOKEN"
+        );
+        // amounts accumulate over rounds, a round without a claim or anything
+        // locked was never funded
+        assert!(
+            account.locking_amount().0 == 0 && account.last_claim_session == 0,
             "ERR_ALREADY_DEPOSITED"
         );
+        assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+        // what was forfeited or reclaimed can't be funded again
+        assert!(account.termination.is_none(), "ERR_ACCOUNT_TERMINATED");
         assert!(
-            account.session_num as u128 * account.release_per_session.0 == amount.0,
-            "ERR_INCORRECT_AMOUNT"
+            account
+                .milestones
+                .iter()
+                .all(|m| m.status != MilestoneStatus::Reclaimed),
+            "ERR_MILESTONE_RECLAIMED"
         );
+        assert!(account.total_amount() == amount.0, "ERR_INCORRECT_AMOUNT");
 
         account.deposited_amount = (account.deposited_amount.0 + amount.0).into();
-        self.data_mut()
-            .accounts
-            .insert(account_id.clone(), account.into());
+        account.funder_id = funder_id;
+        self.internal_update_category(&account, |totals| {
+            totals.deposited = (totals.deposited.0 + amount.0).into();
+            assert!(
+                totals.deposited.0 <= totals.cap.0,
+                "ERR_CATEGORY_CAP_EXCEEDED"
+            );
+        });
+        self.internal_checkpoint_account(&account);
         let data_mut = self.data_mut();
-        let total_balance = data_mut.total_balance.0 + amount.0;
-        // self.data_mut().total_balance += amount;
-        data_mut.total_balance = total_balance.into();
+        data_mut.accounts.insert(account_id.clone(), account.into());
+        // what was settled unfunded is locked now
+        data_mut.settle_cursor = U64(0);
+        self.internal_update_balance(asset, |balance| {
+            balance.total_balance = (balance.total_balance.0 + amount.0).into();
+        });
+    }
+
+    /// Token of a grant added with `options`, None for the default one.
+    pub(crate) fn internal_grant_token(&self, options: &AccountOptions) -> Option<AccountId> {
+        let token_id = options
+            .token_id
+            .clone()
+            .filter(|token_id| token_id != &self.data().token_account_id);
+        if let Some(token_id) = &token_id {
+            self.assert_token_whitelisted(token_id);
+        }
+        assert!(
+            !options.native || token_id.is_none(),
+            "ERR_NATIVE_GRANT_WITH_TOKEN"
+        );
+        assert!(
+            options.category.is_none() || (token_id.is_none() && !options.native),
+            "ERR_ILLEGAL_TOKEN"
+        );
+        token_id
     }
 
     pub fn internal_add_account(
@@ -120,14 +571,45 @@ impl Contract {
         start_timestamp: TimestampSec,
         session_interval: TimestampSec,
         session_num: u32,
-        release_per_session: U128,
+        release_per_session: Option<U128>,
+        options: &AccountOptions,
     ) -> bool {
+        let (release_per_session, remainder_amount) =
+            match (release_per_session, options.total_amount) {
+                (Some(release_per_session), None) => (release_per_session, 0.into()),
+                (None, Some(total_amount)) => {
+                    assert!(session_num > 0, "ERR_INVALID_SESSION_NUM");
+                    let vesting_amount = total_amount
+                        .0
+                        .checked_sub(options.tge_amount.0)
+                        .expect("ERR_INVALID_TGE_AMOUNT");
+                    let session_num = session_num as u128;
+                    (
+                        (vesting_amount / session_num).into(),
+                        (vesting_amount % session_num).into(),
+                    )
+                }
+                _ => env::panic_str("ERR_NEED_EITHER_RELEASE_PER_SESSION_OR_TOTAL_AMOUNT"),
+            };
+        let token_id = self.internal_grant_token(options);
+        // a new or renewed grant has to be settled again before closing
+        self.data_mut().settle_cursor = U64(0);
+        // the TGE is released by an extra first session
+        let session_num = session_num + (options.tge_amount.0 > 0) as u32;
         if let Some(acc) = self.data().accounts.get(&account_id) {
             let mut account = acc.clone().into_current();
             assert!(
-                to_nano(U64(account.start_timestamp.0
-                    + account.session_num as u64 * account.session_interval.0))
-                    < env::block_timestamp(),
+                account.termination.is_some()
+                    || account.expired
+                    || to_nano(U64(account.session_timestamp(account.session_num)))
+                        < env::block_timestamp(),
+                "ERR_ACCOUNT_IN_SESSION"
+            );
+            assert!(
+                account
+                    .milestones
+                    .iter()
+                    .all(|m| m.status != MilestoneStatus::Locked),
                 "ERR_ACCOUNT_IN_SESSION"
             );
             assert_eq!(
@@ -135,47 +617,106 @@ impl Contract {
                 account.unclaimed_amount(env::block_timestamp()),
                 "ERR_ACCOUNT_NEED_CLAIM"
             );
+            // pending amount is withdrawn in the token of the grant
+            assert!(
+                (account.token_id == token_id && account.native == options.native)
+                    || account.pending_amount.0 == 0,
+                "ERR_ACCOUNT_PENDING_NOT_WITHDRAWN"
+            );
             account.start_timestamp = start_timestamp;
             account.session_interval = session_interval;
             account.session_num = session_num;
             account.release_per_session = release_per_session;
+            account.interval_unit = options.interval_unit;
+            account.remainder_amount = remainder_amount;
+            account.remainder_policy = options.remainder_policy;
+            account.milestones = vec![];
+            account.termination_policy = options.termination_policy;
+            account.termination = None;
+            account.accelerated_sessions = 0;
+            account.claim_deadline = options.claim_deadline;
+            account.expired = false;
+            account.token_id = token_id;
+            account.native = options.native;
+            account.tge_amount = options.tge_amount;
+            account.cliff = options.cliff;
+            account.category = options.category.clone();
             account.last_claim_session = 0;
+            self.internal_allocate_to_category(&account, account.total_amount());
+            self.internal_mint_grant_nft(&mut account);
             self.data_mut().accounts.insert(account_id, account.into());
         } else {
-            let account = Account {
-                account_id: account_id.clone(),
+            let mut account = Account::new(
+                account_id.clone(),
                 start_timestamp,
                 session_interval,
                 session_num,
-                last_claim_session: 0,
                 release_per_session,
-                claimed_amount: 0.into(),
-                deposited_amount: 0.into(),
-            };
+            );
+            account.interval_unit = options.interval_unit;
+            account.remainder_amount = remainder_amount;
+            account.remainder_policy = options.remainder_policy;
+            account.termination_policy = options.termination_policy;
+            account.claim_deadline = options.claim_deadline;
+            account.token_id = token_id;
+            account.native = options.native;
+            account.tge_amount = options.tge_amount;
+            account.cliff = options.cliff;
+            account.category = options.category.clone();
+            self.internal_allocate_to_category(&account, account.total_amount());
+            self.internal_mint_grant_nft(&mut account);
             self.data_mut().accounts.insert(account_id, account.into());
         }
         true
     }
-}
 
-#[near]
-impl Contract {
-    #[payable]
-    pub fn claim(&mut self, account_id: Option<AccountId>) -> PromiseOrValue<bool> {
-        let attached: NearToken = env::attached_deposit();
-        assert!(attached == ONE_YOCTO, "ERR_ONE_YOCTO_REQUIRED");
-        let account_id = account_id.unwrap_or(env::predecessor_account_id());
+    /// Moves the grant of `from` with all its state to the fresh key `to`.
+    pub(crate) fn internal_rekey_account(&mut self, from: &AccountId, to: &AccountId) {
+        assert!(
+            !self.data().accounts.contains_key(to),
+            "ERR_ACCOUNT_ALREADY_EXIST"
+        );
         let mut account = self
-            .data()
+            .data_mut()
             .accounts
-            .get(&account_id)
-            .map(|va| va.clone().into_current())
+            .remove(from)
+            .map(|va| va.into_current())
             .expect("ERR_ACCOUNT_NOT_EXIST");
+        // the staking contract knows the grant by its account
+        assert_nothing_staked(&account);
+        // the callback of the transfer looks it up by its account
+        assert_nothing_in_flight(&account);
+        assert_not_frozen(&account);
+        account.account_id = to.clone();
+        // keys and delegates were chosen by the previous holder
+        account.claim_policy = ClaimPolicy::Anyone;
+        account.claim_public_key = None;
+        account.transfer_to = None;
+        account.transfer_approved = false;
+        self.internal_move_grant_nft(&account, from);
+        self.internal_checkpoint_move(&account, from);
+        self.data_mut().accounts.insert(to.clone(), account.into());
+        // the last account took the slot of `from`, settlement is checked again
+        self.data_mut().settle_cursor = U64(0);
+    }
 
+    /// Moves everything currently unlocked for `account_id` out of the vault to `receiver_id`,
+    /// rolling the account back in `after_ft_transfer` if the transfer fails.
+    pub(crate) fn internal_claim(
+        &mut self,
+        account_id: AccountId,
+        mut account: Account,
+        receiver_id: AccountId,
+    ) -> PromiseOrValue<bool> {
         if account.last_claim_session > 0 && account.last_claim_session >= account.session_num {
             // all token has been claimed.
             return PromiseOrValue::Value(false);
         }
+        assert_not_frozen(&account);
+        assert!(
+            !self.internal_is_claim_expired(&account, env::block_timestamp()),
+            "ERR_CLAIM_EXPIRED"
+        );
         let amount = account.unclaimed_amount(env::block_timestamp());
         if amount == 0 {
             return PromiseOrValue::Value(true);
@@ -185,73 +726,206 @@ impl Contract {
             amount <= account.locking_amount().0,
             "ERR_NOT_ENOUGH_BALANCE"
         );
+        // only what is back in the vault
+        assert!(
+            amount <= account.unstaked_amount(),
+            "ERR_STAKED_NOT_WITHDRAWN"
+        );
 
-        let sessions = (amount / account.release_per_session.0) as u32;
-        account.last_claim_session += sessions;
+        let claimed_milestones: Vec<u32> = (0..account.milestones.len() as u32)
+            .filter(|&i| account.milestones[i as usize].status == MilestoneStatus::Released)
+            .collect();
+        if !claimed_milestones.is_empty() {
+            emit_milestone_event(
+                "milestone_claimed",
+                &account_id,
+                &claimed_milestones,
+                amount,
+            );
+        }
+        account.record_claim(env::block_timestamp());
         account.claimed_amount = (account.claimed_amount.0 + amount).into();
+        account.in_flight_amount = (account.in_flight_amount.0 + amount).into();
+        self.internal_update_category(&account, |totals| {
+            totals.claimed = (totals.claimed.0 + amount).into();
+        });
+        self.internal_checkpoint_account(&account);
 
-        let claimed_balance = self.data().claimed_balance.0 + amount;
-        self.data_mut().claimed_balance = claimed_balance.into();
+        let asset = self.internal_account_asset(&account);
+        self.internal_update_balance(&asset, |balance| {
+            balance.claimed_balance = (balance.claimed_balance.0 + amount).into();
+        });
         self.data_mut()
             .accounts
             .insert(account_id.clone(), account.into());
-        // self.data_mut().claimed_balance += amount;
 
         // data_mut.accounts.insert(account_id.clone(), account.into());
         PromiseOrValue::Promise(
-            ext_fungible_token::ext(self.data().token_account_id.clone())
-                .with_attached_deposit(ONE_YOCTO)
-                .with_static_gas(GAS_FOR_FT_TRANSFER)
-                .ft_transfer(
-                    account_id.clone(),
-                    amount.into(),
-                    Some(format!(
-                        "Claiming unlocked {} balance from {}",
-                        amount,
-                        env::current_account_id()
-                    )),
-                )
-                .then(
-                    Self::ext(env::current_account_id())
-                        .with_attached_deposit(NO_DEPOSIT)
-                        .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
-                        .after_ft_transfer(account_id, amount.into()),
+            self.internal_transfer(
+                &asset,
+                receiver_id,
+                amount,
+                format!(
+                    "Claiming unlocked {} balance from {}",
+                    amount,
+                    env::current_account_id()
+                ),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_ft_transfer(account_id, amount.into()),
+            ),
+        )
+    }
+}
+
+#[near]
+impl Contract {
+    #[payable]
+    pub fn claim(&mut self, account_id: Option<AccountId>) -> PromiseOrValue<bool> {
+        let attached: NearToken = env::attached_deposit();
+        assert!(attached == ONE_YOCTO, "ERR_ONE_YOCTO_REQUIRED");
+        let account_id = account_id.unwrap_or(env::predecessor_account_id());
+        let account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(
+            account
+                .claim_policy
+                .allows(&account_id, &env::predecessor_account_id()),
+            "ERR_CLAIM_NOT_ALLOWED"
+        );
+
+        self.internal_claim(account_id.clone(), account, account_id)
+    }
+
+    /// Set who may claim on behalf of the caller's account.
+    /// Attached deposit covers the storage of the delegate list, the rest is refunded.
+    #[payable]
+    pub fn set_claim_policy(&mut self, claim_policy: ClaimPolicy) {
+        assert!(env::attached_deposit() >= ONE_YOCTO, "ERR_DEPOSIT_REQUIRED");
+        let prev_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert_not_frozen(&account);
+        if let ClaimPolicy::Delegates(delegates) = &claim_policy {
+            assert!(
+                delegates.len() <= MAX_CLAIM_DELEGATES,
+                "ERR_TOO_MANY_DELEGATES"
+            );
+        }
+        account.claim_policy = claim_policy;
+        let data_mut = self.data_mut();
+        data_mut.accounts.insert(account_id.clone(), account.into());
+        data_mut.accounts.flush();
+        self.internal_check_storage(prev_storage);
+        log!("{} updated claim policy", account_id);
+    }
+
+    /// Send the pending amount of a previously failed claim to the account,
+    /// once it is able to receive the token (eg. registered with storage_deposit).
+    #[payable]
+    pub fn withdraw_pending(&mut self, account_id: Option<AccountId>) -> PromiseOrValue<bool> {
+        let attached: NearToken = env::attached_deposit();
+        assert!(attached == ONE_YOCTO, "ERR_ONE_YOCTO_REQUIRED");
+        let account_id = account_id.unwrap_or(env::predecessor_account_id());
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(
+            account
+                .claim_policy
+                .allows(&account_id, &env::predecessor_account_id()),
+            "ERR_CLAIM_NOT_ALLOWED"
+        );
+        assert_not_frozen(&account);
+        let amount = account.pending_amount.0;
+        if amount == 0 {
+            return PromiseOrValue::Value(false);
+        }
+        account.pending_amount = 0.into();
+        account.in_flight_amount = (account.in_flight_amount.0 + amount).into();
+        let asset = self.internal_account_asset(&account);
+        self.internal_update_balance(&asset, |balance| {
+            balance.pending_balance = (balance.pending_balance.0 - amount).into();
+        });
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+
+        PromiseOrValue::Promise(
+            self.internal_transfer(
+                &asset,
+                account_id.clone(),
+                amount,
+                format!(
+                    "Withdrawing pending {} balance from {}",
+                    amount,
+                    env::current_account_id()
                 ),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_ft_transfer(account_id, amount.into()),
+            ),
         )
     }
 
+    /// On failure, the amount stays claimed but is kept in the vault as pending,
+    /// so that a claim doesn't need to be redone once the receiver can accept it.
     #[private]
     pub fn after_ft_transfer(&mut self, account_id: AccountId, amount: U128) -> bool {
         let promise_success = is_promise_success();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("The claim is not found");
+        account.in_flight_amount = (account.in_flight_amount.0 - amount.0).into();
         if !promise_success {
-            let mut account = self
-                .data()
-                .accounts
-                .get(&account_id)
-                .map(|va| va.clone().into_current())
-                .expect("The claim is not found");
-            let times = (amount.0 / account.release_per_session.0) as u32;
-            account.last_claim_session -= times;
-            account.claimed_amount = (account.claimed_amount.0 - amount.0).into();
-
-            let claimed_balance = self.data().claimed_balance.0 - amount.0;
-            self.data_mut().claimed_balance = U128(claimed_balance);
-            self.data_mut()
-                .accounts
-                .insert(account_id.clone(), account.into());
+            account.pending_amount = (account.pending_amount.0 + amount.0).into();
+            account.last_claim_error = Some(format!(
+                "ERR_FT_TRANSFER_FAILED: {} could not be sent at {}, receiver may not be registered",
+                amount.0,
+                env::block_timestamp() / 10u64.pow(9)
+            ));
+            let asset = self.internal_account_asset(&account);
+            self.internal_update_balance(&asset, |balance| {
+                balance.pending_balance = (balance.pending_balance.0 + amount.0).into();
+            });
 
             log!(
-                "Account claim failed and rollback, account is {}, balance is {}",
+                "Account claim failed and moved to pending, account is {}, balance is {}",
                 account_id,
                 amount.0
             );
         } else {
+            account.last_claim_error = None;
             log!(
                 "Account claim succeed, account is {}, balance is {}",
                 account_id,
                 amount.0
             );
         }
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
         promise_success
     }
 }
@@ -266,17 +940,48 @@ impl FungibleTokenReceiver for Contract {
         msg: String,
     ) -> PromiseOrValue<U128> {
         let token_in = env::predecessor_account_id();
-        assert_eq!(token_in, self.data().token_account_id, "ERR_ILLEGAL_TOKEN");
+        // the token of the grant is checked on its way back, even if no longer whitelisted
+        if let Some(account_id) = msg.strip_prefix(UNSTAKE_MSG_PREFIX) {
+            return PromiseOrValue::Value(
+                self.internal_receive_unstaked(&sender_id, &token_in, account_id, amount),
+            );
+        }
+        self.assert_token_whitelisted(&token_in);
+        self.assert_not_closed();
+        // pools are in the default token only
+        let is_default_token = token_in == self.data().token_account_id;
 
         if msg.is_empty() {
             env::panic_str("ERR_MISSING_ACCOUNT_ID");
+        } else if msg == BURN_POOL_MSG {
+            assert!(is_default_token, "ERR_ILLEGAL_TOKEN");
+            let burn_pool = self.data().burn_pool.0 + amount.0;
+            self.data_mut().burn_pool = burn_pool.into();
+        } else if msg == MERKLE_POOL_MSG {
+            assert!(is_default_token, "ERR_ILLEGAL_TOKEN");
+            let merkle_pool = self.data().merkle_pool.0 + amount.0;
+            self.data_mut().merkle_pool = merkle_pool.into();
         } else {
+            if !self.internal_is_funder(&sender_id) {
+                log!(
+                    "{} is not a funder, refund deposit to {}, amount: {}",
+                    sender_id,
+                    msg,
+                    amount.0
+                );
+                return PromiseOrValue::Value(amount);
+            }
             let contract_id: AccountId = env::current_account_id();
             if msg.eq_ignore_ascii_case(contract_id.as_str()) {
                 env::panic_str("ERR_RECIPIENT_CANNOT_BE_SESSION_VAULT_CONTRACT");
             }
             let account_id = AccountId::from_str(&msg).expect("ERR_ILL_FORMATTED_ACCOUNT_ID");
-            self.internal_deposit_to_account(&account_id, amount);
+            let funder_id = if self.data().record_funders {
+                Some(sender_id.clone())
+            } else {
+                None
+            };
+            self.internal_deposit_to_account(&account_id, &Asset::Ft(token_in), amount, funder_id);
         }
 
         let sender: AccountId = sender_id;
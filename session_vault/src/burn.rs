@@ -0,0 +1,213 @@
+//! Grants created from token burns on other chains.
+//!
+//! Off-chain attestors watch the burn transactions and each signs a `BurnAttestation`.
+//! Once enough of them agree, anyone can submit it and the destination account gets a
+//! grant (or a top-up of a not yet claimed one) paid from the pre-funded burn pool.
+use crate::account::Account;
+use crate::utils::*;
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{borsh, log, CurveType, PublicKey};
+use std::convert::TryInto;
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+pub struct BurnConfig {
+    // ed25519 keys allowed to attest burns
+    pub attestors: Vec<PublicKey>,
+    // how many distinct attestors must sign an attestation
+    pub threshold: u32,
+    // schedule of the grants created from burns
+    pub start_timestamp: TimestampSec,
+    pub session_interval: TimestampSec,
+    pub session_num: u32,
+}
+
+/// A burn observed on the other chain. Attestors sign the borsh serialization
+/// of `(vault_contract_id, BurnAttestation)`.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+pub struct BurnAttestation {
+    // hash of the burn transaction
+    pub tx_hash: String,
+    // EVM address that burned the tokens
+    pub burner: String,
+    pub amount: U128,
+    // NEAR account receiving the grant
+    pub account_id: AccountId,
+}
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct AttestorSignature {
+    pub public_key: PublicKey,
+    pub signature: Base64VecU8,
+}
+
+impl BurnConfig {
+    fn assert_valid(&self) {
+        assert!(
+            self.threshold > 0 && self.threshold as usize <= self.attestors.len(),
+            "ERR_INVALID_THRESHOLD"
+        );
+        assert!(self.session_num > 0, "ERR_INVALID_SESSION_NUM");
+        for attestor in &self.attestors {
+            assert!(
+                attestor.curve_type() == CurveType::ED25519,
+                "ERR_ONLY_ED25519_SUPPORTED"
+            );
+        }
+    }
+
+    fn assert_attested(&self, attestation: &BurnAttestation, signatures: &[AttestorSignature]) {
+        let message = borsh::to_vec(&(env::current_account_id(), attestation)).unwrap();
+        let mut signers: Vec<&PublicKey> = vec![];
+        for item in signatures {
+            if !self.attestors.contains(&item.public_key) || signers.contains(&&item.public_key) {
+                continue;
+            }
+            let signature: &[u8; 64] = item
+                .signature
+                .0
+                .as_slice()
+                .try_into()
+                .expect("ERR_INVALID_SIGNATURE_LENGTH");
+            let public_key: &[u8; 32] = item.public_key.as_bytes()[1..].try_into().unwrap();
+            if env::ed25519_verify(signature, &message, public_key) {
+                signers.push(&item.public_key);
+            }
+        }
+        assert!(
+            signers.len() >= self.threshold as usize,
+            "ERR_NOT_ENOUGH_ATTESTATIONS"
+        );
+    }
+}
+
+impl Contract {
+    /// Credit the burned amount to its destination account, out of the burn pool.
+    /// Any amount not divisible by the sessions is left in the pool.
+    fn internal_apply_burn(&mut self, config: &BurnConfig, attestation: &BurnAttestation) -> u128 {
+        let account_id = &attestation.account_id;
+        let mut account = match self.data().accounts.get(account_id) {
+            Some(va) => {
+                // only a live time based grant, funded and untouched, can be topped up
+                let account = va.clone().into_current();
+                assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+                assert!(account.termination.is_none(), "ERR_ACCOUNT_TERMINATED");
+                assert!(account.milestones.is_empty(), "ERR_MILESTONE_ACCOUNT");
+                assert!(
+                    account.last_claim_session == 0,
+                    "ERR_ACCOUNT_ALREADY_CLAIMED"
+                );
+                // the burn pool is in the default token
+                assert!(
+                    account.token_id.is_none() && !account.native,
+                    "ERR_ILLEGAL_TOKEN"
+                );
+                // the owner's deposit must be in first, it has to match the total
+                assert!(
+                    account.deposited_amount.0 > 0
+                        && account.locking_amount().0 == account.total_amount(),
+                    "ERR_ACCOUNT_NOT_FUNDED"
+                );
+                account
+            }
+            None => Account::new(
+                account_id.clone(),
+                config.start_timestamp,
+                config.session_interval,
+                config.session_num,
+                0.into(),
+            ),
+        };
+        let per_session = attestation.amount.0 / account.vesting_sessions() as u128;
+        let amount = per_session * account.vesting_sessions() as u128;
+        assert!(amount > 0, "ERR_AMOUNT_TOO_SMALL");
+        self.internal_mint_grant_nft(&mut account);
+        account.release_per_session = (account.release_per_session.0 + per_session).into();
+        account.deposited_amount = (account.deposited_amount.0 + amount).into();
+        self.internal_checkpoint_account(&account);
+        self.internal_allocate_to_category(&account, amount);
+        self.internal_update_category(&account, |totals| {
+            totals.deposited = (totals.deposited.0 + amount).into();
+        });
+
+        let data_mut = self.data_mut();
+        data_mut.burn_pool = data_mut
+            .burn_pool
+            .0
+            .checked_sub(amount)
+            .expect("ERR_NOT_ENOUGH_BURN_POOL")
+            .into();
+        data_mut.accounts.insert(account_id.clone(), account.into());
+        let asset = Asset::Ft(self.data().token_account_id.clone());
+        self.internal_update_balance(&asset, |balance| {
+            balance.total_balance = (balance.total_balance.0 + amount).into();
+        });
+        amount
+    }
+}
+
+#[near]
+impl Contract {
+    /// Set (or disable with None) burn attestation. Only can be called by owner.
+    #[payable]
+    pub fn set_burn_config(&mut self, burn_config: Option<BurnConfig>) {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        if let Some(config) = &burn_config {
+            config.assert_valid();
+        }
+        self.data_mut().burn_config = burn_config;
+        self.internal_check_storage(prev_storage);
+    }
+
+    /// Turn an attested burn into a grant, callable by anyone.
+    /// Each burn tx hash can only be used once.
+    pub fn submit_burn_attestation(
+        &mut self,
+        attestation: BurnAttestation,
+        signatures: Vec<AttestorSignature>,
+    ) -> U128 {
+        self.assert_not_closed();
+        let config = self
+            .data()
+            .burn_config
+            .clone()
+            .expect("ERR_BURN_NOT_ENABLED");
+        config.assert_attested(&attestation, &signatures);
+        parse_evm_address(&attestation.burner.to_lowercase());
+        let tx_hash = attestation.tx_hash.to_lowercase();
+        assert!(
+            self.data_mut().processed_burns.insert(tx_hash.clone()),
+            "ERR_BURN_ALREADY_PROCESSED"
+        );
+
+        let amount = self.internal_apply_burn(&config, &attestation);
+        // the new grant is stored at the expense of the vault
+        let data_mut = self.data_mut();
+        data_mut.accounts.flush();
+        data_mut.nft_tokens.flush();
+        self.assert_native_covered();
+        log!(
+            "Burn {} of {} by {} credited {} to {}",
+            tx_hash,
+            attestation.amount.0,
+            attestation.burner,
+            amount,
+            attestation.account_id
+        );
+        amount.into()
+    }
+
+    pub fn get_burn_config(&self) -> Option<BurnConfig> {
+        self.data().burn_config.clone()
+    }
+
+    pub fn is_burn_processed(&self, tx_hash: String) -> bool {
+        self.data()
+            .processed_burns
+            .contains(&tx_hash.to_lowercase())
+    }
+}
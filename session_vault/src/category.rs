@@ -0,0 +1,148 @@
+//! Tokenomics categories (team, advisors, private sale, ecosystem...) of grants.
+//!
+//! Owner sets a cap per category, a grant tagged with one at `add_account` counts
+//! against it, and so do its deposits. Totals are kept per category for reporting.
+//! Categories are in the default token.
+use crate::account::Account;
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Category {
+    // most that can be allocated to its grants
+    pub cap: U128,
+    // total amount of the rounds of its grants, less what was taken back
+    pub allocated: U128,
+    // deposited to its grants, less what was taken back
+    pub deposited: U128,
+    pub claimed: U128,
+    // owner disabled nft_transfer of its grants
+    pub nft_transfer_disabled: bool,
+}
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct CategoryInfo {
+    pub category: String,
+    #[serde(flatten)]
+    pub totals: Category,
+    // deposited but not claimed yet
+    pub locked: U128,
+}
+
+impl From<(&String, &Category)> for CategoryInfo {
+    fn from((category, totals): (&String, &Category)) -> Self {
+        CategoryInfo {
+            category: category.clone(),
+            totals: totals.clone(),
+            locked: (totals.deposited.0 - totals.claimed.0).into(),
+        }
+    }
+}
+
+impl Contract {
+    /// Apply `f` to the totals of the category of the account, if it has one.
+    pub(crate) fn internal_update_category(
+        &mut self,
+        account: &Account,
+        f: impl FnOnce(&mut Category),
+    ) {
+        if let Some(category) = &account.category {
+            let totals = self
+                .data_mut()
+                .categories
+                .get_mut(category)
+                .expect("ERR_CATEGORY_NOT_EXIST");
+            f(totals);
+        }
+    }
+
+    /// Count the round of the account against its category cap.
+    pub(crate) fn internal_allocate_to_category(&mut self, account: &Account, amount: u128) {
+        self.internal_update_category(account, |totals| {
+            totals.allocated = (totals.allocated.0 + amount).into();
+            assert!(
+                totals.allocated.0 <= totals.cap.0,
+                "ERR_CATEGORY_CAP_EXCEEDED"
+            );
+        });
+    }
+
+    /// Take `amount` taken back from the account off its category,
+    /// off the deposits too if the account was funded.
+    pub(crate) fn internal_deallocate_from_category(
+        &mut self,
+        account: &Account,
+        amount: u128,
+        funded: bool,
+    ) {
+        self.internal_update_category(account, |totals| {
+            totals.allocated = (totals.allocated.0 - amount).into();
+            if funded {
+                totals.deposited = (totals.deposited.0 - amount).into();
+            }
+        });
+    }
+}
+
+#[near]
+impl Contract {
+    /// Add a category or change its cap, which can't be below what is allocated.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_category(&mut self, category: String, cap: U128) {
+        self.assert_owner();
+        assert!(!category.is_empty(), "ERR_INVALID_CATEGORY");
+        let prev_storage = env::storage_usage();
+        let data_mut = self.data_mut();
+        match data_mut.categories.get_mut(&category) {
+            Some(totals) => {
+                assert!(cap.0 >= totals.allocated.0, "ERR_CAP_BELOW_ALLOCATED");
+                totals.cap = cap;
+            }
+            None => {
+                data_mut.categories.insert(
+                    category,
+                    Category {
+                        cap,
+                        allocated: 0.into(),
+                        deposited: 0.into(),
+                        claimed: 0.into(),
+                        nft_transfer_disabled: false,
+                    },
+                );
+            }
+        }
+        data_mut.categories.flush();
+        self.internal_check_storage(prev_storage);
+    }
+
+    /// Remove a category no grant was allocated in. Only can be called by owner.
+    #[payable]
+    pub fn remove_category(&mut self, category: String) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        if let Some(totals) = self.data().categories.get(&category) {
+            assert!(
+                totals.allocated.0 == 0 && totals.deposited.0 == 0,
+                "ERR_CATEGORY_IN_USE"
+            );
+        }
+        self.data_mut().categories.remove(&category).is_some()
+    }
+
+    pub fn get_category(&self, category: String) -> Option<CategoryInfo> {
+        self.data()
+            .categories
+            .get(&category)
+            .map(|totals| (&category, totals).into())
+    }
+
+    /// Allocated, deposited, claimed and locked totals of every category.
+    pub fn get_categories(&self) -> Vec<CategoryInfo> {
+        self.data().categories.iter().map(Into::into).collect()
+    }
+}
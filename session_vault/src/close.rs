@@ -0,0 +1,212 @@
+//! Winding a vault down once its grants are over.
+//!
+//! When every account is fully claimed or expired, owner closes the vault: the tokens
+//! left in it (pools, dust, anything sent to it directly) and the NEAR not needed for
+//! storage go to the treasury, and the vault no longer takes new accounts or deposits.
+//! Accounts are checked page by page with `check_settled` beforehand.
+use crate::account::Account;
+use crate::events::emit_event;
+use crate::milestone::MilestoneStatus;
+use crate::utils::*;
+use crate::*;
+use near_sdk::serde_json::json;
+use near_sdk::{assert_one_yocto, log, Promise, PromiseError, PromiseOrValue};
+
+impl Account {
+    /// Sessions (or milestones) still to unlock or claim, funded or not.
+    fn has_sessions_left(&self) -> bool {
+        if !self.milestones.is_empty() {
+            return self.milestones.iter().any(|m| {
+                matches!(
+                    m.status,
+                    MilestoneStatus::Locked | MilestoneStatus::Released
+                )
+            });
+        }
+        let session_num = self
+            .termination
+            .as_ref()
+            .map(|termination| termination.kept_sessions)
+            .unwrap_or(self.session_num);
+        self.last_claim_session < session_num
+    }
+}
+
+/// An account is settled once expired, or fully claimed and delivered.
+/// An unfunded one with sessions left has to be expired or terminated first.
+fn assert_settled(account_id: &AccountId, account: &Account) {
+    assert!(
+        account.expired || (account.locking_amount().0 == 0 && !account.has_sessions_left()),
+        "ERR_ACCOUNT_NOT_SETTLED: {}",
+        account_id
+    );
+    assert!(
+        account.pending_amount.0 == 0,
+        "ERR_ACCOUNT_PENDING_NOT_WITHDRAWN: {}",
+        account_id
+    );
+    assert!(
+        account.in_flight_amount.0 == 0,
+        "ERR_CLAIM_IN_FLIGHT: {}",
+        account_id
+    );
+    assert!(
+        account.staked_amount.0 == 0 && account.staking_rewards.0 == 0,
+        "ERR_ACCOUNT_STAKING_NOT_WITHDRAWN: {}",
+        account_id
+    );
+}
+
+impl Contract {
+    pub(crate) fn assert_not_closed(&self) {
+        assert!(!self.data().closed, "ERR_VAULT_CLOSED");
+    }
+}
+
+#[near]
+impl Contract {
+    /// Check the next `limit` accounts are settled, from where the previous call stopped.
+    /// Returns the index to continue from, all accounts are checked once it equals
+    /// their number. Only can be called by owner.
+    #[payable]
+    pub fn check_settled(&mut self, limit: Option<u32>) -> U64 {
+        self.assert_owner();
+        assert_one_yocto();
+        let limit = limit.unwrap_or(MAX_CHECK_SETTLED_LIMIT);
+        assert!(
+            limit > 0 && limit <= MAX_CHECK_SETTLED_LIMIT,
+            "ERR_INVALID_LIMIT"
+        );
+        let from_index = self.data().settle_cursor.0;
+        let mut checked = 0;
+        for (account_id, va) in self
+            .data()
+            .accounts
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+        {
+            assert_settled(account_id, &va.clone().into_current());
+            checked += 1;
+        }
+        let next_index = from_index + checked;
+        self.data_mut().settle_cursor = U64(next_index);
+        log!(
+            "{} of {} accounts settled",
+            next_index,
+            self.data().accounts.len()
+        );
+        U64(next_index)
+    }
+
+    /// Mark the vault closed, once check_settled went through all accounts, and
+    /// withdraw what is left to the treasury. Can be called again to retry the withdrawal.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn close_vault(&mut self) -> Promise {
+        self.assert_owner();
+        assert_one_yocto();
+        assert!(
+            self.data().settle_cursor.0 == self.data().accounts.len() as u64,
+            "ERR_SETTLEMENT_NOT_CHECKED"
+        );
+
+        let token_ids: Vec<AccountId> = self.data().token_balances.keys().cloned().collect();
+        let assets = token_ids
+            .iter()
+            .cloned()
+            .map(Asset::Ft)
+            .chain(std::iter::once(Asset::Native));
+        let mut balances = vec![];
+        for asset in assets {
+            let balance = self.internal_balance(&asset);
+            balances.push(json!({
+                "asset": asset.to_string(),
+                "total_balance": balance.total_balance,
+                "claimed_balance": balance.claimed_balance,
+                "treasury_pending": balance.treasury_pending,
+            }));
+            // all of it is part of the balances sent to the treasury
+            self.internal_update_balance(&asset, |balance| {
+                balance.treasury_pending = 0.into();
+            });
+        }
+        let data_mut = self.data_mut();
+        data_mut.closed = true;
+        let (burn_pool, merkle_pool) = (data_mut.burn_pool, data_mut.merkle_pool);
+        data_mut.burn_pool = 0.into();
+        data_mut.merkle_pool = 0.into();
+
+        let storage_cost = env::storage_byte_cost()
+            .checked_mul(env::storage_usage() as u128)
+            .unwrap();
+        let free_balance = env::account_balance().saturating_sub(storage_cost);
+        emit_event(
+            "vault_closed",
+            json!({
+                "accounts": self.data().accounts.len(),
+                "burn_pool": burn_pool,
+                "merkle_pool": merkle_pool,
+                "balances": balances,
+                "native_withdrawn": U128(free_balance.as_yoctonear()),
+            }),
+        );
+        if !free_balance.is_zero() {
+            Promise::new(self.internal_treasury_id()).transfer(free_balance);
+        }
+
+        token_ids
+            .into_iter()
+            .map(|token_id| {
+                ext_fungible_token::ext(token_id.clone())
+                    .with_static_gas(GAS_FOR_FT_BALANCE_OF)
+                    .ft_balance_of(env::current_account_id())
+                    .then(
+                        Self::ext(env::current_account_id())
+                            .with_attached_deposit(NO_DEPOSIT)
+                            .with_static_gas(GAS_FOR_AFTER_CLOSE_VAULT)
+                            .after_close_vault(token_id),
+                    )
+            })
+            .reduce(Promise::and)
+            // the default token is always there
+            .unwrap()
+    }
+
+    pub fn get_settle_cursor(&self) -> U64 {
+        self.data().settle_cursor
+    }
+
+    /// Sends the whole balance of the vault in `token_id` to the treasury.
+    #[private]
+    pub fn after_close_vault(
+        &mut self,
+        token_id: AccountId,
+        #[callback_result] balance: Result<U128, PromiseError>,
+    ) -> PromiseOrValue<bool> {
+        match balance {
+            Ok(balance) if balance.0 > 0 => {
+                emit_event(
+                    "vault_token_withdrawn",
+                    json!({
+                        "token_id": token_id,
+                        "amount": balance,
+                    }),
+                );
+                PromiseOrValue::Promise(self.internal_send_to_treasury(
+                    Asset::Ft(token_id),
+                    balance.0,
+                    format!("Closing {}", env::current_account_id()),
+                ))
+            }
+            Ok(_) => PromiseOrValue::Value(true),
+            Err(_) => {
+                log!(
+                    "Balance of closed vault in {} unknown, close_vault again to retry",
+                    token_id
+                );
+                PromiseOrValue::Value(false)
+            }
+        }
+    }
+}
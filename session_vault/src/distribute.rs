@@ -0,0 +1,80 @@
+//! Push distribution by whitelisted keepers, so that unlocked tokens reach
+//! beneficiaries who never claim. A keeper sweeps `accounts` page by page,
+//! and the contract remembers where the last page ended.
+use crate::utils::*;
+use crate::*;
+use near_sdk::log;
+
+#[near]
+impl Contract {
+    /// Push every currently unlocked amount for a page of accounts, one transfer each.
+    /// Starts from `from_index`, or where the previous call stopped if None.
+    /// Accounts whose claim policy doesn't allow the keeper, frozen ones, and those
+    /// whose unlocked amount is still staked are skipped.
+    /// Returns the index the next call would start from, 0 once the sweep wrapped around.
+    pub fn distribute(&mut self, from_index: Option<U64>, limit: Option<u32>) -> U64 {
+        let keeper_id = env::predecessor_account_id();
+        assert!(self.data().keepers.contains(&keeper_id), "ERR_NOT_ALLOWED");
+        let limit = limit.unwrap_or(MAX_DISTRIBUTE_LIMIT);
+        assert!(
+            limit > 0 && limit <= MAX_DISTRIBUTE_LIMIT,
+            "ERR_INVALID_LIMIT"
+        );
+        let from_index = from_index.unwrap_or(self.data().distribute_cursor).0;
+        let account_ids: Vec<AccountId> = self
+            .data()
+            .accounts
+            .keys()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+
+        let cur_ts = env::block_timestamp();
+        let mut distributed = 0;
+        for account_id in &account_ids {
+            let account = self
+                .data()
+                .accounts
+                .get(account_id)
+                .map(|va| va.clone().into_current())
+                .unwrap();
+            let amount = account.unclaimed_amount(cur_ts);
+            if amount == 0
+                || amount > account.unstaked_amount()
+                || self.internal_is_claim_expired(&account, cur_ts)
+                || account.freeze.is_some()
+                || !account.claim_policy.allows(account_id, &keeper_id)
+            {
+                continue;
+            }
+            // the promise is scheduled when dropped
+            let _ = self.internal_claim(account_id.clone(), account, account_id.clone());
+            distributed += 1;
+        }
+
+        let next_index = from_index + account_ids.len() as u64;
+        let next_index = if next_index >= self.data().accounts.len() as u64 {
+            0
+        } else {
+            next_index
+        };
+        self.data_mut().distribute_cursor = U64(next_index);
+        log!(
+            "{} distributed to {} of {} accounts from index {}",
+            keeper_id,
+            distributed,
+            account_ids.len(),
+            from_index
+        );
+        U64(next_index)
+    }
+
+    pub fn get_keepers(&self) -> Vec<AccountId> {
+        self.data().keepers.iter().cloned().collect()
+    }
+
+    pub fn get_distribute_cursor(&self) -> U64 {
+        self.data().distribute_cursor
+    }
+}
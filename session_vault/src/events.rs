@@ -0,0 +1,17 @@
+//! NEP-297 events of the vault, logged as `EVENT_JSON:` followed by the event.
+use near_sdk::log;
+use near_sdk::serde_json::{json, Value};
+
+const EVENT_STANDARD: &str = "session_vault";
+const EVENT_VERSION: &str = "1.0.0";
+
+/// Log `event` of the vault with a single `data` entry.
+pub(crate) fn emit_event(event: &str, data: Value) {
+    let event = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_VERSION,
+        "event": event,
+        "data": [data],
+    });
+    log!("EVENT_JSON:{}", event);
+}
@@ -0,0 +1,41 @@
+//! Grants for cross-chain claimants that only have an EVM wallet.
+//!
+//! The owner adds the grant under the EVM address itself (`0x` + 40 lowercase hex,
+//! a valid NEAR account id), and funds it as usual. The holder of that EVM key then
+//! binds it to a NEAR account by signing (EIP-191 `personal_sign`) the message
+//! returned by `get_evm_link_message`, which moves the whole grant to that NEAR account.
+use crate::utils::*;
+use crate::*;
+use near_sdk::log;
+
+#[near]
+impl Contract {
+    /// The message an EVM address signs to bind its grant to `account_id`.
+    pub fn get_evm_link_message(&self, evm_address: String, account_id: AccountId) -> String {
+        format!(
+            "Link {} to NEAR account {} on {}",
+            evm_address.to_lowercase(),
+            account_id,
+            env::current_account_id()
+        )
+    }
+
+    /// Bind the grant held by `evm_address` to `account_id`.
+    /// Anyone can submit it, authorization comes from the 65 bytes hex `signature`.
+    pub fn link_evm_account(
+        &mut self,
+        evm_address: String,
+        account_id: AccountId,
+        signature: String,
+    ) {
+        let evm_address = evm_address.to_lowercase();
+        let evm_account_id: AccountId = evm_address.parse().expect("ERR_INVALID_EVM_ADDRESS");
+        let expected = parse_evm_address(&evm_address);
+        let message = self.get_evm_link_message(evm_address.clone(), account_id.clone());
+        let signer = recover_evm_signer(message.as_bytes(), &signature);
+        assert!(signer == expected, "ERR_INVALID_SIGNATURE");
+
+        self.internal_rekey_account(&evm_account_id, &account_id);
+        log!("{} linked to {}", evm_address, account_id);
+    }
+}
@@ -0,0 +1,116 @@
+//! Claim deadlines, so allocations of lost wallets don't stay locked forever.
+//!
+//! A deadline can be set for the whole vault and overridden per account. Once it
+//! has passed, the account can no longer claim and owner can sweep what is left
+//! of it to the treasury, marking it expired.
+use crate::account::Account;
+use crate::staking::assert_nothing_staked;
+use crate::utils::*;
+use crate::*;
+use near_sdk::{assert_one_yocto, log, PromiseOrValue};
+
+impl Contract {
+    /// Deadline of the account, or the vault one if it has none.
+    pub(crate) fn internal_claim_deadline(&self, account: &Account) -> Option<TimestampSec> {
+        account.claim_deadline.or(self.data().claim_deadline)
+    }
+
+    pub(crate) fn internal_is_claim_expired(&self, account: &Account, cur_ts: u64) -> bool {
+        account.expired
+            || self
+                .internal_claim_deadline(account)
+                .map(|deadline| to_nano(deadline) < cur_ts)
+                .unwrap_or(false)
+    }
+}
+
+pub(crate) fn assert_valid_claim_deadline(claim_deadline: Option<TimestampSec>) {
+    if let Some(deadline) = claim_deadline {
+        assert!(
+            to_nano(deadline) > env::block_timestamp(),
+            "ERR_INVALID_CLAIM_DEADLINE"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Set (or remove with None) the claim deadline of accounts without their own.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_claim_deadline(&mut self, claim_deadline: Option<TimestampSec>) {
+        self.assert_owner();
+        assert_one_yocto();
+        assert_valid_claim_deadline(claim_deadline);
+        self.data_mut().claim_deadline = claim_deadline;
+    }
+
+    /// Set (or remove with None) the claim deadline of an account.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_account_claim_deadline(
+        &mut self,
+        account_id: AccountId,
+        claim_deadline: Option<TimestampSec>,
+    ) {
+        self.assert_owner();
+        assert_one_yocto();
+        assert_valid_claim_deadline(claim_deadline);
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+        account.claim_deadline = claim_deadline;
+        self.data_mut().accounts.insert(account_id, account.into());
+    }
+
+    /// Send what is left in an account past its claim deadline to the treasury,
+    /// and mark it expired. A failed transfer goes to the treasury pending.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn sweep_expired_account(&mut self, account_id: AccountId) -> PromiseOrValue<bool> {
+        self.assert_owner();
+        assert_one_yocto();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+        assert!(
+            self.internal_is_claim_expired(&account, env::block_timestamp()),
+            "ERR_CLAIM_DEADLINE_NOT_PASSED"
+        );
+        assert_nothing_staked(&account);
+        let amount = account.locking_amount().0;
+        account.expired = true;
+        account.deposited_amount = (account.deposited_amount.0 - amount).into();
+        log!("{} expired, swept amount: {}", account_id, amount);
+        let asset = self.internal_account_asset(&account);
+        self.internal_deallocate_from_category(&account, amount, true);
+        self.internal_checkpoint_account(&account);
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        if amount == 0 {
+            return PromiseOrValue::Value(true);
+        }
+
+        self.internal_update_balance(&asset, |balance| {
+            balance.total_balance = (balance.total_balance.0 - amount).into();
+        });
+        PromiseOrValue::Promise(self.internal_send_to_treasury(
+            asset,
+            amount,
+            format!(
+                "Sweeping expired {} from {}",
+                account_id,
+                env::current_account_id()
+            ),
+        ))
+    }
+}
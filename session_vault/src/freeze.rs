@@ -0,0 +1,120 @@
+//! Compliance holds on single grants, eg. pending a sanctions review.
+//!
+//! Owner or a compliance officer freezes an account with a reason code. A frozen
+//! account can't claim, withdraw, be transferred or change who claims for it, while
+//! its sessions keep unlocking as usual. Each freeze and unfreeze emits an event.
+use crate::account::Account;
+use crate::events::emit_event;
+use crate::utils::TimestampSec;
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde_json::json;
+
+/// Why and by whom an account was frozen.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Freeze {
+    pub reason_code: String,
+    pub frozen_by: AccountId,
+    pub timestamp: TimestampSec,
+}
+
+pub(crate) fn assert_not_frozen(account: &Account) {
+    assert!(account.freeze.is_none(), "ERR_ACCOUNT_FROZEN");
+}
+
+/// Event of a freeze or unfreeze.
+fn emit_freeze_event(event: &str, account_id: &AccountId, reason_code: &str) {
+    emit_event(
+        event,
+        json!({
+            "account_id": account_id,
+            "reason_code": reason_code,
+            "by": env::predecessor_account_id(),
+        }),
+    );
+}
+
+impl Contract {
+    fn assert_compliance(&self) {
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == self.data().owner_id
+                || self.data().compliance_officers.contains(&caller_id),
+            "ERR_NOT_ALLOWED"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Allow an account to freeze and unfreeze accounts. Only can be called by owner.
+    #[payable]
+    pub fn add_compliance_officer(&mut self, officer_id: AccountId) -> bool {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().compliance_officers.insert(officer_id);
+        self.data_mut().compliance_officers.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_compliance_officer(&mut self, officer_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().compliance_officers.remove(&officer_id)
+    }
+
+    /// Put a hold on an account, recording why.
+    /// Can be called by owner or a compliance officer.
+    #[payable]
+    pub fn freeze_account(&mut self, account_id: AccountId, reason_code: String) {
+        self.assert_compliance();
+        assert_one_yocto();
+        assert!(!reason_code.is_empty(), "ERR_INVALID_REASON_CODE");
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert_not_frozen(&account);
+        account.freeze = Some(Freeze {
+            reason_code: reason_code.clone(),
+            frozen_by: env::predecessor_account_id(),
+            timestamp: U64(env::block_timestamp() / 10u64.pow(9)),
+        });
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        emit_freeze_event("account_frozen", &account_id, &reason_code);
+    }
+
+    /// Lift the hold on an account, `reason_code` telling why, eg. the review cleared it.
+    /// Can be called by owner or a compliance officer.
+    #[payable]
+    pub fn unfreeze_account(&mut self, account_id: AccountId, reason_code: String) {
+        self.assert_compliance();
+        assert_one_yocto();
+        assert!(!reason_code.is_empty(), "ERR_INVALID_REASON_CODE");
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(account.freeze.is_some(), "ERR_ACCOUNT_NOT_FROZEN");
+        account.freeze = None;
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        emit_freeze_event("account_unfrozen", &account_id, &reason_code);
+    }
+
+    pub fn get_compliance_officers(&self) -> Vec<AccountId> {
+        self.data().compliance_officers.iter().cloned().collect()
+    }
+}
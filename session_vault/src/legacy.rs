@@ -0,0 +1,82 @@
+//! Previous versions of the persisted structures, kept so that
+//! state written by older releases can still be read and upgraded.
+use crate::account::VAccount;
+use crate::utils::TimestampSec;
+use crate::{ContractData, StorageKeys, TokenBalance};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::store::{IterableMap, IterableSet, LookupMap, LookupSet};
+use near_sdk::{near, AccountId};
+
+/// Contract data layout of session_vault 1.0.0.
+#[near(serializers = [borsh])]
+pub struct ContractDataV0 {
+    pub owner_id: AccountId,
+    pub token_account_id: AccountId,
+    pub total_balance: U128,
+    pub claimed_balance: U128,
+    pub accounts: IterableMap<AccountId, VAccount>,
+}
+
+impl From<ContractDataV0> for ContractData {
+    fn from(data: ContractDataV0) -> Self {
+        let mut tokens = IterableSet::new(StorageKeys::Tokens);
+        tokens.insert(data.token_account_id.clone());
+        let mut token_balances = IterableMap::new(StorageKeys::TokenBalances);
+        token_balances.insert(
+            data.token_account_id.clone(),
+            TokenBalance {
+                total_balance: data.total_balance,
+                claimed_balance: data.claimed_balance,
+                ..Default::default()
+            },
+        );
+        ContractData {
+            owner_id: data.owner_id,
+            token_account_id: data.token_account_id,
+            accounts: data.accounts,
+            burn_config: None,
+            burn_pool: 0.into(),
+            processed_burns: LookupSet::new(StorageKeys::ProcessedBurns),
+            merkle_root: None,
+            merkle_pool: 0.into(),
+            merkle_claimed: LookupSet::new(StorageKeys::MerkleClaimed),
+            keepers: IterableSet::new(StorageKeys::Keepers),
+            distribute_cursor: U64(0),
+            transfer_requires_approval: false,
+            milestone_attesters: IterableSet::new(StorageKeys::MilestoneAttesters),
+            treasury_id: None,
+            claim_deadline: None,
+            closed: false,
+            settle_cursor: U64(0),
+            funders: IterableSet::new(StorageKeys::Funders),
+            record_funders: false,
+            tokens,
+            token_balances,
+            native_balance: TokenBalance::default(),
+            factory_id: None,
+            templates: IterableMap::new(StorageKeys::Templates),
+            categories: IterableMap::new(StorageKeys::Categories),
+            nft_enabled: false,
+            nft_tokens: IterableMap::new(StorageKeys::NftTokens),
+            voting_checkpoints: false,
+            checkpoints: LookupMap::new(StorageKeys::Checkpoints),
+            staking_contracts: IterableSet::new(StorageKeys::StakingContracts),
+            beneficiary_staking: false,
+            compliance_officers: IterableSet::new(StorageKeys::ComplianceOfficers),
+        }
+    }
+}
+
+/// Account layout of session_vault 1.0.0.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+pub struct AccountV0 {
+    pub account_id: AccountId,
+    pub start_timestamp: TimestampSec,
+    pub session_interval: TimestampSec,
+    pub session_num: u32,
+    pub last_claim_session: u32,
+    pub release_per_session: U128,
+    pub claimed_amount: U128,
+    pub deposited_amount: U128,
+}
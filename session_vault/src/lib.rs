@@ -5,20 +5,68 @@
 
 use near_sdk::borsh::BorshSerialize;
 use near_sdk::json_types::{U128, U64};
-use near_sdk::store::IterableMap;
+use near_sdk::store::{IterableMap, IterableSet, LookupMap, LookupSet};
 use near_sdk::{env, near, AccountId, BorshStorageKey, PanicOnDefault};
 
 use crate::account::VAccount;
+pub use crate::account::{AccountOptions, ClaimPolicy, IntervalUnit, RemainderPolicy};
+pub use crate::burn::{AttestorSignature, BurnAttestation, BurnConfig};
+pub use crate::category::{Category, CategoryInfo};
+pub use crate::freeze::Freeze;
+use crate::legacy::ContractDataV0;
+pub use crate::merkle::MerkleAllocation;
+pub use crate::milestone::{Milestone, MilestoneConfig, MilestoneStatus};
+pub use crate::signed_claim::ClaimPayload;
+pub use crate::template::{TemplateInfo, VestingTemplate};
+pub use crate::termination::{Termination, TerminationKind, TerminationPolicy};
+pub use crate::tokens::{Asset, TokenBalance, TokenInfo};
+use crate::utils::TimestampSec;
 pub use crate::views::ContractInfo;
+pub use crate::voting::{Checkpoint, VotingPower};
+mod acceleration;
 mod account;
+mod burn;
+mod category;
+mod close;
+mod distribute;
+mod events;
+mod evm_link;
+mod expiry;
+mod freeze;
+mod legacy;
+mod merkle;
+mod milestone;
+mod native;
+mod nft;
 mod owner;
+mod signed_claim;
+mod staking;
+mod template;
+mod termination;
+mod tokens;
+mod transfer;
+mod treasury;
 mod utils;
 mod views;
+mod voting;
 
 #[derive(BorshSerialize, BorshStorageKey)]
 #[borsh(crate = "near_sdk::borsh")]
 pub enum StorageKeys {
     Accounts,
+    ProcessedBurns,
+    MerkleClaimed,
+    Keepers,
+    MilestoneAttesters,
+    Funders,
+    Tokens,
+    TokenBalances,
+    Templates,
+    Categories,
+    NftTokens,
+    Checkpoints,
+    StakingContracts,
+    ComplianceOfficers,
 }
 
 #[near(serializers = [borsh])]
@@ -26,20 +74,102 @@ pub struct ContractData {
     // owner of this contract
     owner_id: AccountId,
 
-    // token kept by this vault
+    // default token of grants, and the one of burn and merkle pools
     token_account_id: AccountId,
 
-    // the total deposited amount in this vault
-    total_balance: U128,
+    accounts: IterableMap<AccountId, VAccount>,
 
-    // already claimed balance
-    claimed_balance: U128,
+    // attestors and schedule for grants created from burns on other chains
+    burn_config: Option<BurnConfig>,
 
-    accounts: IterableMap<AccountId, VAccount>,
+    // pre-funded balance that burn grants are paid from
+    burn_pool: U128,
+
+    // EVM tx hashes of burns already turned into grants
+    processed_burns: LookupSet<String>,
+
+    // root of the published bulk allocations
+    merkle_root: Option<[u8; 32]>,
+
+    // pre-funded balance that merkle allocations are paid from
+    merkle_pool: U128,
+
+    // leaves of merkle allocations already activated
+    merkle_claimed: LookupSet<Vec<u8>>,
+
+    // accounts allowed to push distributions
+    keepers: IterableSet<AccountId>,
+
+    // index in accounts where the next distribution page starts
+    distribute_cursor: U64,
+
+    // whether account transfers need owner approval
+    transfer_requires_approval: bool,
+
+    // accounts allowed to mark milestones complete
+    milestone_attesters: IterableSet<AccountId>,
+
+    // receives tokens taken back from grants, owner if None
+    treasury_id: Option<AccountId>,
+
+    // no claim after it, for accounts without their own
+    claim_deadline: Option<TimestampSec>,
+
+    // wound down by close_vault, no new account or deposit
+    closed: bool,
+    // index in accounts up to which check_settled found them settled
+    settle_cursor: U64,
+
+    // accounts besides owner allowed to fund accounts
+    funders: IterableSet<AccountId>,
+
+    // whether deposits record their sender on the account
+    record_funders: bool,
+
+    // tokens new grants can be in, the default one included
+    tokens: IterableSet<AccountId>,
+
+    // balances of every token the vault has held grants in
+    token_balances: IterableMap<AccountId, TokenBalance>,
+
+    // balances of grants in native NEAR
+    native_balance: TokenBalance,
+
+    // factory that deployed this vault, allowed to upgrade it
+    factory_id: Option<AccountId>,
+
+    // schedules of the sale rounds, by name
+    templates: IterableMap<String, VestingTemplate>,
+
+    // tokenomics categories of grants, with their caps and totals
+    categories: IterableMap<String, Category>,
+
+    // whether new grants are minted a NEP-171 token, and tokens can be transferred
+    nft_enabled: bool,
+
+    // holder of the token of each grant, by token id
+    nft_tokens: IterableMap<String, AccountId>,
+
+    // whether what accounts hold is recorded by block height
+    voting_checkpoints: bool,
+
+    // what each account held in the vault, from block heights on
+    checkpoints: LookupMap<AccountId, Vec<Checkpoint>>,
+
+    // contracts the locked balance of grants can be staked in
+    staking_contracts: IterableSet<AccountId>,
+
+    // whether beneficiaries can stake their own grants, besides owner
+    beneficiary_staking: bool,
+
+    // accounts besides owner allowed to freeze and unfreeze accounts
+    compliance_officers: IterableSet<AccountId>,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[near(serializers = [borsh])]
 pub enum VContractData {
+    V0(ContractDataV0),
     Current(ContractData),
 }
 
@@ -51,20 +181,59 @@ pub struct Contract {
 
 #[near]
 impl Contract {
-    #[private]
+    /// Called by the vault itself, or by the factory creating it as a sub-account.
     #[init]
     pub fn new(owner_id: String, token_id: String) -> Self {
         let owner_id: AccountId = owner_id.parse().expect("ERR_INVALID_ACCOUNT_ID_OWNER");
         let token_id: AccountId = token_id.parse().expect("ERR_INVALID_ACCOUNT_ID_TOKEN");
-        let total_balance: U128 = U128::from(0);
-        let claimed_balance: U128 = U128::from(0);
+        let predecessor_id = env::predecessor_account_id();
+        let factory_id = if predecessor_id == env::current_account_id() {
+            None
+        } else {
+            assert!(
+                env::current_account_id().is_sub_account_of(&predecessor_id),
+                "ERR_NOT_ALLOWED"
+            );
+            Some(predecessor_id)
+        };
+        let mut tokens = IterableSet::new(StorageKeys::Tokens);
+        tokens.insert(token_id.clone());
+        let mut token_balances = IterableMap::new(StorageKeys::TokenBalances);
+        token_balances.insert(token_id.clone(), TokenBalance::default());
         Self {
             data: VContractData::Current(ContractData {
                 owner_id,
                 token_account_id: token_id,
-                total_balance,
-                claimed_balance,
                 accounts: IterableMap::new(StorageKeys::Accounts),
+                burn_config: None,
+                burn_pool: 0.into(),
+                processed_burns: LookupSet::new(StorageKeys::ProcessedBurns),
+                merkle_root: None,
+                merkle_pool: 0.into(),
+                merkle_claimed: LookupSet::new(StorageKeys::MerkleClaimed),
+                keepers: IterableSet::new(StorageKeys::Keepers),
+                distribute_cursor: U64(0),
+                transfer_requires_approval: false,
+                milestone_attesters: IterableSet::new(StorageKeys::MilestoneAttesters),
+                treasury_id: None,
+                claim_deadline: None,
+                closed: false,
+                settle_cursor: U64(0),
+                funders: IterableSet::new(StorageKeys::Funders),
+                record_funders: false,
+                tokens,
+                token_balances,
+                native_balance: TokenBalance::default(),
+                factory_id,
+                templates: IterableMap::new(StorageKeys::Templates),
+                categories: IterableMap::new(StorageKeys::Categories),
+                nft_enabled: false,
+                nft_tokens: IterableMap::new(StorageKeys::NftTokens),
+                voting_checkpoints: false,
+                checkpoints: LookupMap::new(StorageKeys::Checkpoints),
+                staking_contracts: IterableSet::new(StorageKeys::StakingContracts),
+                beneficiary_staking: false,
+                compliance_officers: IterableSet::new(StorageKeys::ComplianceOfficers),
             }),
         }
     }
@@ -74,12 +243,14 @@ impl Contract {
     fn data(&self) -> &ContractData {
         match &self.data {
             VContractData::Current(data) => data,
+            _ => env::panic_str("ERR_CONTRACT_NOT_MIGRATED"),
         }
     }
 
     fn data_mut(&mut self) -> &mut ContractData {
         match &mut self.data {
             VContractData::Current(data) => data,
+            _ => env::panic_str("ERR_CONTRACT_NOT_MIGRATED"),
         }
     }
 }
@@ -0,0 +1,132 @@
+//! Bulk allocations published as a Merkle root instead of one `add_account` each.
+//!
+//! Each leaf is `sha256(borsh(account_id, MerkleAllocation))`, and parents hash the
+//! sorted pair of their children, `sha256(min(a, b) || max(a, b))`. The owner funds
+//! the merkle pool once, and an account is only created the first time its
+//! beneficiary claims with a valid proof. From then on it is a regular account.
+use crate::account::Account;
+use crate::utils::*;
+use crate::*;
+use near_sdk::{assert_one_yocto, log, PromiseOrValue};
+use std::convert::TryInto;
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+pub struct MerkleAllocation {
+    pub start_timestamp: TimestampSec,
+    pub session_interval: TimestampSec,
+    pub session_num: u32,
+    // total amount, should be divisible by session_num
+    pub amount: U128,
+}
+
+type Hash = [u8; 32];
+
+fn parse_hash(value: &str) -> Hash {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .expect("ERR_INVALID_MERKLE_HASH")
+        .try_into()
+        .expect("ERR_INVALID_MERKLE_HASH")
+}
+
+fn merkle_leaf(account_id: &AccountId, allocation: &MerkleAllocation) -> Hash {
+    env::sha256_array(&near_sdk::borsh::to_vec(&(account_id, allocation)).unwrap())
+}
+
+fn merkle_root_of(leaf: Hash, proof: &[String]) -> Hash {
+    proof
+        .iter()
+        .map(|p| parse_hash(p))
+        .fold(leaf, |node, sibling| {
+            let (a, b) = if node <= sibling {
+                (node, sibling)
+            } else {
+                (sibling, node)
+            };
+            env::sha256_array(&[a, b].concat())
+        })
+}
+
+#[near]
+impl Contract {
+    /// Publish (or remove with None) the Merkle root of allocations. Only can be called by owner.
+    #[payable]
+    pub fn set_merkle_root(&mut self, merkle_root: Option<String>) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().merkle_root = merkle_root.map(|root| parse_hash(&root));
+    }
+
+    /// First claim of a Merkle allocation, creating the caller's account.
+    /// Attached deposit covers the storage of the new account, the rest is refunded.
+    #[payable]
+    pub fn claim_with_proof(
+        &mut self,
+        allocation: MerkleAllocation,
+        proof: Vec<String>,
+    ) -> PromiseOrValue<bool> {
+        assert!(env::attached_deposit() >= ONE_YOCTO, "ERR_DEPOSIT_REQUIRED");
+        self.assert_not_closed();
+        let prev_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let root = self.data().merkle_root.expect("ERR_MERKLE_NOT_ENABLED");
+        assert!(
+            !self.data().accounts.contains_key(&account_id),
+            "ERR_ACCOUNT_ALREADY_EXIST"
+        );
+        assert!(allocation.session_num > 0, "ERR_INVALID_SESSION_NUM");
+        let release_per_session = allocation.amount.0 / allocation.session_num as u128;
+        assert!(
+            allocation.session_num as u128 * release_per_session == allocation.amount.0,
+            "ERR_INCORRECT_AMOUNT"
+        );
+        let leaf = merkle_leaf(&account_id, &allocation);
+        assert!(
+            merkle_root_of(leaf, &proof) == root,
+            "ERR_INVALID_MERKLE_PROOF"
+        );
+
+        let mut account = Account::new(
+            account_id.clone(),
+            allocation.start_timestamp,
+            allocation.session_interval,
+            allocation.session_num,
+            release_per_session.into(),
+        );
+        account.deposited_amount = allocation.amount;
+        self.internal_mint_grant_nft(&mut account);
+        self.internal_checkpoint_account(&account);
+        let data_mut = self.data_mut();
+        assert!(
+            data_mut.merkle_claimed.insert(leaf.to_vec()),
+            "ERR_ALLOCATION_ALREADY_CLAIMED"
+        );
+        data_mut.merkle_pool = data_mut
+            .merkle_pool
+            .0
+            .checked_sub(allocation.amount.0)
+            .expect("ERR_NOT_ENOUGH_MERKLE_POOL")
+            .into();
+        data_mut
+            .accounts
+            .insert(account_id.clone(), (&account).into());
+        let asset = Asset::Ft(data_mut.token_account_id.clone());
+        self.internal_update_balance(&asset, |balance| {
+            balance.total_balance = (balance.total_balance.0 + allocation.amount.0).into();
+        });
+        self.data_mut().accounts.flush();
+        self.data_mut().token_balances.flush();
+        self.internal_check_storage(prev_storage);
+        log!(
+            "{} activated merkle allocation of {}",
+            account_id,
+            allocation.amount.0
+        );
+
+        self.internal_claim(account_id.clone(), account, account_id)
+    }
+
+    pub fn get_merkle_root(&self) -> Option<String> {
+        self.data().merkle_root.map(hex::encode)
+    }
+}
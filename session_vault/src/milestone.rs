@@ -0,0 +1,251 @@
+//! Grants whose tranches unlock on milestones (mainnet launch, integration shipped...)
+//! instead of time.
+//!
+//! Each milestone of such an account is a session of its own, released when an
+//! authorised attester marks it complete. Released milestones are then claimed like
+//! unlocked sessions. A milestone still locked after its deadline can be reclaimed by owner
+//! to the treasury.
+use crate::account::{Account, AccountOptions};
+use crate::events::emit_event;
+use crate::expiry::assert_valid_claim_deadline;
+use crate::staking::assert_nothing_staked;
+use crate::utils::*;
+use crate::*;
+use near_sdk::serde_json::json;
+use near_sdk::{assert_one_yocto, PromiseOrValue};
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+pub struct MilestoneConfig {
+    pub description: String,
+    pub amount: U128,
+    // after this time, a still locked milestone can be reclaimed by owner
+    pub deadline: Option<TimestampSec>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum MilestoneStatus {
+    // waiting for an attester
+    Locked,
+    // marked complete, claimable
+    Released,
+    // released and claimed
+    Claimed,
+    // deadline missed, taken back by owner
+    Reclaimed,
+}
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Milestone {
+    pub description: String,
+    pub amount: U128,
+    pub deadline: Option<TimestampSec>,
+    pub status: MilestoneStatus,
+}
+
+impl From<MilestoneConfig> for Milestone {
+    fn from(config: MilestoneConfig) -> Self {
+        Milestone {
+            description: config.description,
+            amount: config.amount,
+            deadline: config.deadline,
+            status: MilestoneStatus::Locked,
+        }
+    }
+}
+
+impl Milestone {
+    fn is_expired(&self, cur_ts: u64) -> bool {
+        self.deadline
+            .map(|deadline| to_nano(deadline) < cur_ts)
+            .unwrap_or(false)
+    }
+}
+
+/// Event of milestones changing status, `amount` being their total.
+pub(crate) fn emit_milestone_event(
+    event: &str,
+    account_id: &AccountId,
+    milestone_indexes: &[u32],
+    amount: u128,
+) {
+    emit_event(
+        event,
+        json!({
+            "account_id": account_id,
+            "milestone_indexes": milestone_indexes,
+            "amount": U128(amount),
+            "by": env::predecessor_account_id(),
+        }),
+    );
+}
+
+impl Contract {
+    fn internal_get_milestone_account(&self, account_id: &AccountId) -> Account {
+        let account = self
+            .data()
+            .accounts
+            .get(account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(!account.milestones.is_empty(), "ERR_NOT_MILESTONE_ACCOUNT");
+        account
+    }
+}
+
+#[near]
+impl Contract {
+    /// Add an account released by milestones, funded like any other account
+    /// with the sum of the milestone amounts. Of the options, those of the time
+    /// schedule don't apply: total_amount, tge_amount and cliff must be left unset,
+    /// interval_unit and remainder_policy are ignored. Only can be called by owner.
+    #[payable]
+    pub fn add_milestone_account(
+        &mut self,
+        account_id: AccountId,
+        milestones: Vec<MilestoneConfig>,
+        options: Option<AccountOptions>,
+    ) -> bool {
+        self.assert_owner();
+        self.assert_not_closed();
+        let prev_storage = env::storage_usage();
+        assert!(
+            !self.data().accounts.contains_key(&account_id),
+            "ERR_ACCOUNT_ALREADY_EXIST"
+        );
+        assert!(
+            !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+            "ERR_INVALID_MILESTONES"
+        );
+        let options = options.unwrap_or_default();
+        assert!(
+            options.total_amount.is_none() && options.tge_amount.0 == 0 && options.cliff.0 == 0,
+            "ERR_INVALID_MILESTONE_OPTIONS"
+        );
+        assert_valid_claim_deadline(options.claim_deadline);
+        let token_id = self.internal_grant_token(&options);
+        let mut account = Account::new(
+            account_id.clone(),
+            U64(env::block_timestamp() / 10u64.pow(9)),
+            U64(0),
+            milestones.len() as u32,
+            0.into(),
+        );
+        account.milestones = milestones.into_iter().map(Into::into).collect();
+        account.termination_policy = options.termination_policy;
+        account.claim_deadline = options.claim_deadline;
+        account.token_id = token_id;
+        account.native = options.native;
+        account.category = options.category;
+        self.internal_allocate_to_category(&account, account.total_amount());
+        self.internal_mint_grant_nft(&mut account);
+        let data_mut = self.data_mut();
+        data_mut.accounts.insert(account_id, account.into());
+        // a new grant has to be settled again before closing
+        data_mut.settle_cursor = U64(0);
+        data_mut.accounts.flush();
+        self.internal_check_storage(prev_storage);
+        true
+    }
+
+    /// Mark a milestone complete, releasing its tranche. Only can be called by attesters.
+    pub fn release_milestone(&mut self, account_id: AccountId, milestone_index: u32) {
+        let attester_id = env::predecessor_account_id();
+        assert!(
+            self.data().milestone_attesters.contains(&attester_id),
+            "ERR_NOT_ALLOWED"
+        );
+        let mut account = self.internal_get_milestone_account(&account_id);
+        let milestone = account
+            .milestones
+            .get_mut(milestone_index as usize)
+            .expect("ERR_MILESTONE_NOT_EXIST");
+        assert!(
+            milestone.status == MilestoneStatus::Locked,
+            "ERR_MILESTONE_NOT_LOCKED"
+        );
+        assert!(
+            !milestone.is_expired(env::block_timestamp()),
+            "ERR_MILESTONE_EXPIRED"
+        );
+        milestone.status = MilestoneStatus::Released;
+        emit_milestone_event(
+            "milestone_released",
+            &account_id,
+            &[milestone_index],
+            milestone.amount.0,
+        );
+        self.data_mut().accounts.insert(account_id, account.into());
+    }
+
+    /// Take back the tranche of a milestone not released before its deadline.
+    /// If the account was funded, the tranche is sent to the treasury, a failed
+    /// transfer going to the treasury pending. Only can be called by owner.
+    #[payable]
+    pub fn reclaim_milestone(
+        &mut self,
+        account_id: AccountId,
+        milestone_index: u32,
+    ) -> PromiseOrValue<bool> {
+        self.assert_owner();
+        assert_one_yocto();
+        let mut account = self.internal_get_milestone_account(&account_id);
+        // a swept account has nothing left to take back
+        assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+        assert_nothing_staked(&account);
+        let funded = account.deposited_amount.0 > 0;
+        let milestone = account
+            .milestones
+            .get_mut(milestone_index as usize)
+            .expect("ERR_MILESTONE_NOT_EXIST");
+        assert!(
+            milestone.status == MilestoneStatus::Locked,
+            "ERR_MILESTONE_NOT_LOCKED"
+        );
+        assert!(
+            milestone.is_expired(env::block_timestamp()),
+            "ERR_MILESTONE_NOT_EXPIRED"
+        );
+        milestone.status = MilestoneStatus::Reclaimed;
+        let amount = milestone.amount.0;
+        emit_milestone_event(
+            "milestone_reclaimed",
+            &account_id,
+            &[milestone_index],
+            amount,
+        );
+        self.internal_deallocate_from_category(&account, amount, funded);
+        if !funded {
+            self.data_mut().accounts.insert(account_id, account.into());
+            return PromiseOrValue::Value(true);
+        }
+
+        account.deposited_amount = (account.deposited_amount.0 - amount).into();
+        self.internal_checkpoint_account(&account);
+        let asset = self.internal_account_asset(&account);
+        self.internal_update_balance(&asset, |balance| {
+            balance.total_balance = (balance.total_balance.0 - amount).into();
+        });
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        PromiseOrValue::Promise(self.internal_send_to_treasury(
+            asset,
+            amount,
+            format!(
+                "Reclaiming milestone {} of {} from {}",
+                milestone_index,
+                account_id,
+                env::current_account_id()
+            ),
+        ))
+    }
+
+    pub fn get_milestone_attesters(&self) -> Vec<AccountId> {
+        self.data().milestone_attesters.iter().cloned().collect()
+    }
+}
@@ -0,0 +1,74 @@
+//! Grants in native NEAR, for contributors not paid in the token.
+//!
+//! Such a grant is added with `options.native` and funded by attaching its total amount
+//! to `fund_account_native`. It follows the same sessions as token grants, and is claimed
+//! with plain NEAR transfers. Its balances are kept apart from the token ones.
+//! The NEAR they hold is not available for storage: what is taken without a deposit
+//! (burn grants, voting checkpoints) is paid from the rest of the vault balance.
+use crate::tokens::Asset;
+use crate::*;
+use near_sdk::{log, Promise};
+
+impl Contract {
+    /// NEAR owed by the vault on native grants: deposited and not claimed, claimed but
+    /// not delivered, or not sent to the treasury yet.
+    fn internal_native_held(&self) -> u128 {
+        let balance = &self.data().native_balance;
+        balance.total_balance.0 - balance.claimed_balance.0
+            + balance.pending_balance.0
+            + balance.treasury_pending.0
+    }
+
+    /// Storage taken without a deposit can't draw on the NEAR of native grants.
+    /// Collections have to be flushed before, for the storage usage to be up to date.
+    pub(crate) fn assert_native_covered(&self) {
+        let storage_cost = env::storage_byte_cost()
+            .checked_mul(env::storage_usage() as u128)
+            .unwrap();
+        assert!(
+            env::account_balance().as_yoctonear()
+                >= storage_cost.as_yoctonear() + self.internal_native_held(),
+            "ERR_NATIVE_BALANCE_NOT_COVERED"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Fund a native grant with the attached NEAR, which should be its total amount.
+    /// As for tokens, only owner and funders can fund, anyone else is refunded.
+    #[payable]
+    pub fn fund_account_native(&mut self, account_id: AccountId) -> bool {
+        self.assert_not_closed();
+        let sender_id = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        if !self.internal_is_funder(&sender_id) {
+            log!(
+                "{} is not a funder, refund deposit to {}, amount: {}",
+                sender_id,
+                account_id,
+                amount.as_yoctonear()
+            );
+            Promise::new(sender_id).transfer(amount);
+            return false;
+        }
+        let funder_id = if self.data().record_funders {
+            Some(sender_id.clone())
+        } else {
+            None
+        };
+        self.internal_deposit_to_account(
+            &account_id,
+            &Asset::Native,
+            amount.as_yoctonear().into(),
+            funder_id,
+        );
+        log!(
+            "{} deposit NEAR to {}, amount: {}",
+            sender_id,
+            account_id,
+            amount.as_yoctonear()
+        );
+        true
+    }
+}
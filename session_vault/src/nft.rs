@@ -0,0 +1,289 @@
+//! Grants as NEP-171 tokens, so that locked allocations can be moved or sold OTC.
+//!
+//! Once owner enables it, each new grant is minted a token held by its beneficiary.
+//! Moving the token with `nft_transfer` moves the whole grant to the receiver, like an
+//! accepted account transfer, so the holder of the token is the one who can claim.
+//! Owner can disable transfers of a grant, or of every grant of a category.
+use crate::account::Account;
+use crate::utils::*;
+use crate::*;
+use near_contract_standards::non_fungible_token::core::ext_nft_receiver;
+use near_contract_standards::non_fungible_token::events::{NftMint, NftTransfer};
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, TokenMetadata, NFT_METADATA_SPEC,
+};
+use near_contract_standards::non_fungible_token::{Token, TokenId};
+use near_sdk::{assert_one_yocto, log, serde_json, PromiseError, PromiseOrValue};
+use std::collections::HashMap;
+
+impl Contract {
+    /// Mint the token of a grant, if enabled and it has none yet.
+    pub(crate) fn internal_mint_grant_nft(&mut self, account: &mut Account) {
+        if !self.data().nft_enabled || account.nft_token_id.is_some() {
+            return;
+        }
+        let data_mut = self.data_mut();
+        let token_id = data_mut.nft_tokens.len().to_string();
+        data_mut
+            .nft_tokens
+            .insert(token_id.clone(), account.account_id.clone());
+        NftMint {
+            owner_id: &account.account_id,
+            token_ids: &[&token_id],
+            memo: None,
+        }
+        .emit();
+        account.nft_token_id = Some(token_id);
+    }
+
+    /// Record the new holder of the token of a grant moved from `old_owner_id`.
+    pub(crate) fn internal_move_grant_nft(&mut self, account: &Account, old_owner_id: &AccountId) {
+        if let Some(token_id) = &account.nft_token_id {
+            self.data_mut()
+                .nft_tokens
+                .insert(token_id.clone(), account.account_id.clone());
+            NftTransfer {
+                old_owner_id,
+                new_owner_id: &account.account_id,
+                token_ids: &[token_id],
+                authorized_id: None,
+                memo: None,
+            }
+            .emit();
+        }
+    }
+
+    fn internal_nft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+    ) {
+        assert!(self.data().nft_enabled, "ERR_NFT_DISABLED");
+        assert!(approval_id.is_none(), "ERR_APPROVAL_NOT_SUPPORTED");
+        let owner_id = self
+            .data()
+            .nft_tokens
+            .get(token_id)
+            .expect("ERR_TOKEN_NOT_EXIST");
+        assert!(owner_id == sender_id, "ERR_NOT_TOKEN_OWNER");
+        assert!(sender_id != receiver_id, "ERR_SAME_ACCOUNT");
+        let account = self
+            .data()
+            .accounts
+            .get(sender_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        let category_disabled = account
+            .category
+            .as_ref()
+            .and_then(|category| self.data().categories.get(category))
+            .map(|category| category.nft_transfer_disabled)
+            .unwrap_or(false);
+        assert!(
+            !account.nft_transfer_disabled && !category_disabled,
+            "ERR_NFT_TRANSFER_DISABLED"
+        );
+        // failed claims are the sender's
+        assert!(
+            account.pending_amount.0 == 0,
+            "ERR_ACCOUNT_PENDING_NOT_WITHDRAWN"
+        );
+        self.internal_rekey_account(sender_id, receiver_id);
+        log!("Grant {} transferred to {}", token_id, receiver_id);
+    }
+
+    fn internal_nft_token(&self, token_id: &TokenId) -> Option<Token> {
+        let owner_id = self.data().nft_tokens.get(token_id)?;
+        let account_info = self.internal_account_info(self.data().accounts.get(owner_id)?);
+        let asset = if account_info.native {
+            "NEAR".to_string()
+        } else {
+            account_info.token_id.as_ref().unwrap().to_string()
+        };
+        Some(Token {
+            token_id: token_id.clone(),
+            owner_id: owner_id.clone(),
+            metadata: Some(TokenMetadata {
+                title: Some(format!("Vesting grant #{}", token_id)),
+                description: Some(format!(
+                    "{} {} over {} sessions from {} to {}",
+                    account_info.total_amount.0,
+                    asset,
+                    account_info.session_num,
+                    account_info.start_timestamp.0,
+                    account_info.end_timestamp.0
+                )),
+                extra: Some(serde_json::to_string(&account_info).unwrap()),
+                ..Default::default()
+            }),
+            approved_account_ids: None,
+        })
+    }
+}
+
+#[near]
+impl Contract {
+    /// Mint tokens for grants added from now on, or stop minting and transfers.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_nft_enabled(&mut self, enabled: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().nft_enabled = enabled;
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_account_nft_transferable(&mut self, account_id: AccountId, transferable: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        account.nft_transfer_disabled = !transferable;
+        self.data_mut().accounts.insert(account_id, account.into());
+    }
+
+    /// Applies to every grant of the category, on top of their own setting.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_category_nft_transferable(&mut self, category: String, transferable: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut()
+            .categories
+            .get_mut(&category)
+            .expect("ERR_CATEGORY_NOT_EXIST")
+            .nft_transfer_disabled = !transferable;
+    }
+
+    /// Move the grant of the token to `receiver_id`, which must not have one.
+    /// Approvals are not supported.
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(&sender_id, &receiver_id, &token_id, approval_id);
+        if let Some(memo) = memo {
+            log!("Memo: {}", memo);
+        }
+    }
+
+    /// Move the grant of the token and call `nft_on_transfer` on the receiver,
+    /// which returns true to give it back.
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(&sender_id, &receiver_id, &token_id, approval_id);
+        if let Some(memo) = memo {
+            log!("Memo: {}", memo);
+        }
+        ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_NFT_ON_TRANSFER)
+            .nft_on_transfer(sender_id.clone(), sender_id.clone(), token_id.clone(), msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_NFT_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(sender_id, receiver_id, token_id, None),
+            )
+            .into()
+    }
+
+    /// Gives the grant back to the sender if the receiver asked so, or failed, unless
+    /// the sender has got a new grant meanwhile or the receiver staked it or was frozen.
+    /// Returns whether the grant stays with the receiver.
+    #[private]
+    pub fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        #[allow(unused_variables)] approved_account_ids: Option<HashMap<AccountId, u64>>,
+        #[callback_result] return_token: Result<bool, PromiseError>,
+    ) -> bool {
+        if let Ok(false) = return_token {
+            return true;
+        }
+        let receiver_locked = self
+            .data()
+            .accounts
+            .get(&receiver_id)
+            .map(|va| {
+                let account = va.clone().into_current();
+                account.staked_amount.0 > 0
+                    || account.in_flight_amount.0 > 0
+                    || account.freeze.is_some()
+            })
+            .unwrap_or(false);
+        if self.data().nft_tokens.get(&token_id) != Some(&receiver_id)
+            || self.data().accounts.contains_key(&previous_owner_id)
+            || receiver_locked
+        {
+            log!("Grant {} kept by {}", token_id, receiver_id);
+            return true;
+        }
+        self.internal_rekey_account(&receiver_id, &previous_owner_id);
+        log!("Grant {} returned to {}", token_id, previous_owner_id);
+        false
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.internal_nft_token(&token_id)
+    }
+
+    pub fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: format!("Vesting grants of {}", env::current_account_id()),
+            symbol: "GRANT".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    pub fn nft_total_supply(&self) -> U128 {
+        (self.data().nft_tokens.len() as u128).into()
+    }
+
+    /// The token of the grant of `account_id`, each holder has at most one.
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let skip = from_index.map(|i| i.0).unwrap_or(0);
+        if skip > 0 || limit == Some(0) {
+            return vec![];
+        }
+        self.data()
+            .accounts
+            .get(&account_id)
+            .and_then(|va| va.clone().into_current().nft_token_id)
+            .and_then(|token_id| self.internal_nft_token(&token_id))
+            .into_iter()
+            .collect()
+    }
+}
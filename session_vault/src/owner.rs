@@ -1,14 +1,13 @@
 //! Implement all the relevant logic for owner of this contract.
-use crate::utils::TimestampSec;
+use crate::expiry::assert_valid_claim_deadline;
+use crate::utils::{TimestampSec, GAS_FOR_MIGRATE_CALL, NO_DEPOSIT};
 use crate::*;
 use near_sdk::{assert_one_yocto, json_types::U128, near, NearToken, Promise, StorageUsage};
 
 impl Contract {
     /// Check how much storage taken costs and refund the left over back.
-    fn internal_check_storage(&self, prev_storage: StorageUsage) {
-        let storage = env::storage_usage()
-            .checked_sub(prev_storage)
-            .unwrap_or_default() as u128;
+    pub(crate) fn internal_check_storage(&self, prev_storage: StorageUsage) {
+        let storage = env::storage_usage().saturating_sub(prev_storage) as u128;
         let storage_cost = env::storage_byte_cost().checked_mul(storage).unwrap();
 
         let msg = format!(
@@ -41,6 +40,71 @@ impl Contract {
         self.data().owner_id.clone()
     }
 
+    /// Allow an account to push distributions. Only can be called by owner.
+    #[payable]
+    pub fn add_keeper(&mut self, keeper_id: AccountId) -> bool {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().keepers.insert(keeper_id);
+        self.data_mut().keepers.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_keeper(&mut self, keeper_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().keepers.remove(&keeper_id)
+    }
+
+    /// Allow an account to fund accounts. Only can be called by owner.
+    #[payable]
+    pub fn add_funder(&mut self, funder_id: AccountId) -> bool {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().funders.insert(funder_id);
+        self.data_mut().funders.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_funder(&mut self, funder_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().funders.remove(&funder_id)
+    }
+
+    /// Whether deposits record their sender on the account. Only can be called by owner.
+    #[payable]
+    pub fn set_record_funders(&mut self, record_funders: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().record_funders = record_funders;
+    }
+
+    /// Allow an account to mark milestones complete. Only can be called by owner.
+    #[payable]
+    pub fn add_milestone_attester(&mut self, attester_id: AccountId) -> bool {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().milestone_attesters.insert(attester_id);
+        self.data_mut().milestone_attesters.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_milestone_attester(&mut self, attester_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().milestone_attesters.remove(&attester_id)
+    }
+
     #[payable]
     pub fn add_account(
         &mut self,
@@ -48,17 +112,25 @@ impl Contract {
         start_timestamp: TimestampSec,
         session_interval: TimestampSec,
         session_num: u32,
-        release_per_session: U128,
+        release_per_session: Option<U128>,
+        options: Option<AccountOptions>,
     ) -> bool {
         let account_id: AccountId = account_id.parse().expect("ERR_ACCOUNT_ID_IS_INVALID");
         let prev_storage = env::storage_usage();
         self.assert_owner();
+        self.assert_not_closed();
+        let options = options.unwrap_or_default();
+        if matches!(options.interval_unit, IntervalUnit::Month) {
+            assert!(session_interval.0 > 0, "ERR_INVALID_SESSION_INTERVAL");
+        }
+        assert_valid_claim_deadline(options.claim_deadline);
         let ret = self.internal_add_account(
             account_id,
             start_timestamp,
             session_interval,
             session_num,
             release_per_session,
+            &options,
         );
         self.internal_check_storage(prev_storage);
         ret
@@ -72,12 +144,40 @@ impl Contract {
         );
     }
 
+    /// Deploy the code passed as raw input, then migrate to it.
+    /// Only can be called by owner, or by the factory that deployed this vault.
+    #[payable]
+    pub fn upgrade(&mut self) -> Promise {
+        assert_one_yocto();
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == self.data().owner_id
+                || self.data().factory_id.as_ref() == Some(&caller_id),
+            "ERR_NOT_ALLOWED"
+        );
+        let code = env::input().expect("ERR_NO_CODE");
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                "migrate".to_string(),
+                vec![],
+                NO_DEPOSIT,
+                GAS_FOR_MIGRATE_CALL,
+            )
+    }
+
     /// Migration function.
     /// For next version upgrades, change this function.
     #[init(ignore_state)]
     #[private]
     pub fn migrate() -> Self {
         let prev: Contract = env::state_read().expect("ERR_NOT_INITIALIZED");
-        prev
+        let data = match prev.data {
+            VContractData::V0(data) => data.into(),
+            VContractData::Current(data) => data,
+        };
+        Contract {
+            data: VContractData::Current(data),
+        }
     }
 }
@@ -0,0 +1,107 @@
+//! Claims authorized off-chain by the beneficiary and submitted by a relayer,
+//! so that beneficiaries without NEAR for gas can still claim.
+use crate::account::Account;
+use crate::freeze::assert_not_frozen;
+use crate::utils::*;
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{borsh, log, CurveType, PromiseOrValue, PublicKey};
+use std::convert::TryInto;
+
+/// What the beneficiary signs. The signed message is the borsh serialization of
+/// `(vault_contract_id, ClaimPayload)`, so a signature is only valid on one vault.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+pub struct ClaimPayload {
+    pub account_id: AccountId,
+    // who receives the claimed tokens
+    pub receiver_id: AccountId,
+    // must be greater than the account's last used claim nonce
+    pub nonce: U64,
+    // signature is rejected after this time
+    pub expires_at: TimestampSec,
+}
+
+impl Account {
+    fn assert_claim_signature(&self, payload: &ClaimPayload, signature: &[u8]) {
+        let public_key = self
+            .claim_public_key
+            .as_ref()
+            .expect("ERR_NO_CLAIM_PUBLIC_KEY");
+        let signature: &[u8; 64] = signature.try_into().expect("ERR_INVALID_SIGNATURE_LENGTH");
+        let public_key: &[u8; 32] = public_key.as_bytes()[1..]
+            .try_into()
+            .expect("ERR_INVALID_PUBLIC_KEY");
+        let message = borsh::to_vec(&(env::current_account_id(), payload)).unwrap();
+        assert!(
+            env::ed25519_verify(signature, &message, public_key),
+            "ERR_INVALID_SIGNATURE"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Register (or remove with None) the ed25519 key that signs claims for the caller's account.
+    #[payable]
+    pub fn set_claim_public_key(&mut self, public_key: Option<PublicKey>) {
+        assert!(env::attached_deposit() >= ONE_YOCTO, "ERR_DEPOSIT_REQUIRED");
+        if let Some(public_key) = &public_key {
+            assert!(
+                public_key.curve_type() == CurveType::ED25519,
+                "ERR_ONLY_ED25519_SUPPORTED"
+            );
+        }
+        let prev_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert_not_frozen(&account);
+        account.claim_public_key = public_key;
+        let data_mut = self.data_mut();
+        data_mut.accounts.insert(account_id.clone(), account.into());
+        data_mut.accounts.flush();
+        self.internal_check_storage(prev_storage);
+        log!("{} updated claim public key", account_id);
+    }
+
+    /// Claim with a beneficiary-signed payload, callable by anyone (typically a relayer).
+    /// The claim policy doesn't apply, as the signature is the beneficiary's own authorization.
+    pub fn claim_with_signature(
+        &mut self,
+        payload: ClaimPayload,
+        signature: Base64VecU8,
+    ) -> PromiseOrValue<bool> {
+        assert!(
+            env::block_timestamp() <= to_nano(payload.expires_at),
+            "ERR_SIGNATURE_EXPIRED"
+        );
+        let mut account = self
+            .data()
+            .accounts
+            .get(&payload.account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(
+            payload.nonce.0 > account.claim_nonce.0,
+            "ERR_NONCE_ALREADY_USED"
+        );
+        account.assert_claim_signature(&payload, &signature.0);
+        account.claim_nonce = payload.nonce;
+        self.data_mut()
+            .accounts
+            .insert(payload.account_id.clone(), (&account).into());
+
+        log!(
+            "Signed claim of {} to {} relayed by {}",
+            payload.account_id,
+            payload.receiver_id,
+            env::predecessor_account_id()
+        );
+        self.internal_claim(payload.account_id, account, payload.receiver_id)
+    }
+}
@@ -0,0 +1,294 @@
+//! Staking the locked tokens of a grant, so that they earn while vesting.
+//!
+//! Owner whitelists staking contracts, and can stake the locked balance of a grant in one
+//! of them, as can the beneficiary if the vault allows it. The vault stakes with
+//! `ft_transfer_call`, msg being the beneficiary, and the tokens count as locked all along.
+//! `unstake` calls `unstake(account_id, amount)` of the staking contract (see
+//! `ExtStakingContract`), which sends them back with what they earned by `ft_transfer_call`,
+//! msg being `UNSTAKE:<account_id>`. What arrives is the principal up to the staked amount,
+//! the rest is credited to the beneficiary, who withdraws it with `withdraw_staking_rewards`.
+//! A claim is never paid out of the stake: it fails with ERR_STAKED_NOT_WITHDRAWN until
+//! enough is unstaked to cover all it unlocked.
+use crate::account::Account;
+use crate::freeze::assert_not_frozen;
+use crate::utils::*;
+use crate::*;
+use near_sdk::{assert_one_yocto, log, Promise, PromiseError, PromiseOrValue};
+use std::str::FromStr;
+
+impl Contract {
+    fn internal_get_account(&self, account_id: &AccountId) -> Account {
+        self.data()
+            .accounts
+            .get(account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST")
+    }
+
+    /// Tokens sent back by the staking contract of the account, the principal up to the
+    /// staked amount and the rest as rewards. Returns the amount refunded, all of it if
+    /// the sender is not where the account stakes.
+    pub(crate) fn internal_receive_unstaked(
+        &mut self,
+        staking_id: &AccountId,
+        token_id: &AccountId,
+        account_id: &str,
+        amount: U128,
+    ) -> U128 {
+        let staked = AccountId::from_str(account_id).ok().and_then(|account_id| {
+            self.data()
+                .accounts
+                .get(&account_id)
+                .map(|va| (account_id, va.clone().into_current()))
+        });
+        let (account_id, mut account) = match staked {
+            Some((account_id, account))
+                if account.staking_id.as_ref() == Some(staking_id)
+                    && self.internal_account_asset(&account) == Asset::Ft(token_id.clone()) =>
+            {
+                (account_id, account)
+            }
+            _ => {
+                log!(
+                    "{} is not staked in {}, refund unstake, amount: {}",
+                    account_id,
+                    staking_id,
+                    amount.0
+                );
+                return amount;
+            }
+        };
+        let principal = amount.0.min(account.staked_amount.0);
+        let rewards = amount.0 - principal;
+        account.staked_amount = (account.staked_amount.0 - principal).into();
+        if account.staked_amount.0 == 0 {
+            account.staking_id = None;
+        }
+        account.staking_rewards = (account.staking_rewards.0 + rewards).into();
+        self.internal_update_balance(&Asset::Ft(token_id.clone()), |balance| {
+            balance.total_balance = (balance.total_balance.0 + rewards).into();
+        });
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        log!(
+            "Unstaked {} of {}, rewards: {}",
+            principal,
+            account_id,
+            rewards
+        );
+        0.into()
+    }
+
+    /// Owner, or the beneficiary if the vault allows it.
+    fn assert_can_stake(&self, account_id: &AccountId) {
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == self.data().owner_id
+                || (&caller_id == account_id && self.data().beneficiary_staking),
+            "ERR_NOT_ALLOWED"
+        );
+    }
+}
+
+pub(crate) fn assert_nothing_staked(account: &Account) {
+    assert!(account.staked_amount.0 == 0, "ERR_STAKED_NOT_WITHDRAWN");
+}
+
+#[near]
+impl Contract {
+    /// Allow grants to be staked in a contract. Only can be called by owner.
+    #[payable]
+    pub fn add_staking_contract(&mut self, staking_id: AccountId) -> bool {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().staking_contracts.insert(staking_id);
+        self.data_mut().staking_contracts.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Stop new stakes in a contract, what is staked in it can still be unstaked.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_staking_contract(&mut self, staking_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().staking_contracts.remove(&staking_id)
+    }
+
+    /// Whether beneficiaries can stake their own grants. Only can be called by owner.
+    #[payable]
+    pub fn set_beneficiary_staking(&mut self, allowed: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().beneficiary_staking = allowed;
+    }
+
+    /// Stake `amount` of the locked balance of the grant in a whitelisted contract.
+    /// The unlocked but unclaimed amount can't be staked, and a grant stakes in
+    /// one contract at a time.
+    #[payable]
+    pub fn stake(&mut self, account_id: AccountId, staking_id: AccountId, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.assert_can_stake(&account_id);
+        assert!(
+            self.data().staking_contracts.contains(&staking_id),
+            "ERR_STAKING_CONTRACT_NOT_WHITELISTED"
+        );
+        let mut account = self.internal_get_account(&account_id);
+        let token_id = match self.internal_account_asset(&account) {
+            Asset::Ft(token_id) => token_id,
+            Asset::Native => env::panic_str("ERR_ILLEGAL_TOKEN"),
+        };
+        let cur_ts = env::block_timestamp();
+        assert_not_frozen(&account);
+        assert!(
+            !self.internal_is_claim_expired(&account, cur_ts),
+            "ERR_CLAIM_EXPIRED"
+        );
+        assert!(
+            account.staked_amount.0 == 0 || account.staking_id.as_ref() == Some(&staking_id),
+            "ERR_STAKED_ELSEWHERE"
+        );
+        assert!(amount.0 > 0, "ERR_INVALID_AMOUNT");
+        let stakable = account.unstaked_amount() - account.unclaimed_amount(cur_ts);
+        assert!(amount.0 <= stakable, "ERR_NOT_ENOUGH_LOCKED");
+
+        account.staking_id = Some(staking_id.clone());
+        account.staked_amount = (account.staked_amount.0 + amount.0).into();
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        log!("Staking {} of {} in {}", amount.0, account_id, staking_id);
+
+        ext_fungible_token::ext(token_id)
+            .with_attached_deposit(ONE_YOCTO)
+            .with_static_gas(GAS_FOR_STAKE)
+            .ft_transfer_call(
+                staking_id,
+                amount,
+                Some(format!("Staking locked {} of {}", amount.0, account_id)),
+                account_id.to_string(),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_STAKE)
+                    .after_stake(account_id, amount),
+            )
+    }
+
+    /// Takes what the staking contract refunded off the staked amount.
+    /// Returns the amount staked.
+    #[private]
+    pub fn after_stake(
+        &mut self,
+        account_id: AccountId,
+        amount: U128,
+        #[callback_result] used_amount: Result<U128, PromiseError>,
+    ) -> U128 {
+        let used_amount = used_amount.map(|used| used.0.min(amount.0)).unwrap_or(0);
+        let refunded = amount.0 - used_amount;
+        if refunded > 0 {
+            let mut account = self.internal_get_account(&account_id);
+            account.staked_amount = (account.staked_amount.0 - refunded).into();
+            if account.staked_amount.0 == 0 {
+                account.staking_id = None;
+            }
+            self.data_mut()
+                .accounts
+                .insert(account_id.clone(), account.into());
+            log!("Stake of {} refunded, amount: {}", account_id, refunded);
+        }
+        used_amount.into()
+    }
+
+    /// Ask the staking contract to send `amount` staked by the grant back, with its rewards.
+    /// They are credited as they arrive, what doesn't come back stays staked.
+    /// Can be called by owner or the beneficiary.
+    #[payable]
+    pub fn unstake(&mut self, account_id: AccountId, amount: U128) -> Promise {
+        assert_one_yocto();
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == self.data().owner_id || caller_id == account_id,
+            "ERR_NOT_ALLOWED"
+        );
+        let account = self.internal_get_account(&account_id);
+        // owner can still bring a frozen grant back into the vault
+        if caller_id != self.data().owner_id {
+            assert_not_frozen(&account);
+        }
+        assert!(
+            amount.0 > 0 && amount.0 <= account.staked_amount.0,
+            "ERR_INVALID_AMOUNT"
+        );
+        let staking_id = account.staking_id.unwrap();
+        log!(
+            "Unstaking {} of {} from {}",
+            amount.0,
+            account_id,
+            staking_id
+        );
+
+        ext_staking_contract::ext(staking_id)
+            .with_static_gas(GAS_FOR_UNSTAKE)
+            .unstake(account_id, amount)
+    }
+
+    /// Send the staking rewards credited to the account to it.
+    /// A failed transfer moves them to the pending amount.
+    #[payable]
+    pub fn withdraw_staking_rewards(
+        &mut self,
+        account_id: Option<AccountId>,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        let account_id = account_id.unwrap_or(env::predecessor_account_id());
+        let mut account = self.internal_get_account(&account_id);
+        assert!(
+            account
+                .claim_policy
+                .allows(&account_id, &env::predecessor_account_id()),
+            "ERR_CLAIM_NOT_ALLOWED"
+        );
+        assert_not_frozen(&account);
+        let amount = account.staking_rewards.0;
+        if amount == 0 {
+            return PromiseOrValue::Value(false);
+        }
+        account.staking_rewards = 0.into();
+        account.in_flight_amount = (account.in_flight_amount.0 + amount).into();
+        let asset = self.internal_account_asset(&account);
+        self.internal_update_balance(&asset, |balance| {
+            balance.claimed_balance = (balance.claimed_balance.0 + amount).into();
+        });
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+
+        PromiseOrValue::Promise(
+            self.internal_transfer(
+                &asset,
+                account_id.clone(),
+                amount,
+                format!(
+                    "Withdrawing staking rewards {} from {}",
+                    amount,
+                    env::current_account_id()
+                ),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_ft_transfer(account_id, amount.into()),
+            ),
+        )
+    }
+
+    pub fn get_staking_contracts(&self) -> Vec<AccountId> {
+        self.data().staking_contracts.iter().cloned().collect()
+    }
+}
@@ -0,0 +1,120 @@
+//! Named schedules of a sale round (strategic, private, public...), so that every
+//! beneficiary of the round gets identical terms.
+//!
+//! A template holds the cliff, interval, session count and TGE share of the round,
+//! `add_account_from_template` turns it and a total amount into a grant.
+use crate::utils::*;
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct VestingTemplate {
+    // in interval_unit, sessions due before start + cliff unlock at its end
+    pub cliff: TimestampSec,
+    pub session_interval: TimestampSec,
+    // vesting sessions, the TGE one excluded
+    pub session_num: u32,
+    // share of the total released at start, in basis points
+    pub tge_bps: u32,
+    #[serde(default)]
+    pub interval_unit: IntervalUnit,
+    // tokenomics category of the grants of the round
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TemplateInfo {
+    pub template_id: String,
+    #[serde(flatten)]
+    pub template: VestingTemplate,
+}
+
+#[near]
+impl Contract {
+    /// Add or replace a template, grants already made from it keep their terms.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn set_template(&mut self, template_id: String, template: VestingTemplate) -> bool {
+        self.assert_owner();
+        assert!(!template_id.is_empty(), "ERR_INVALID_TEMPLATE_ID");
+        assert!(
+            template.session_interval.0 > 0 && template.session_num > 0,
+            "ERR_INVALID_TEMPLATE"
+        );
+        assert!(template.tge_bps <= BPS_DENOMINATOR, "ERR_INVALID_TGE_SHARE");
+        let prev_storage = env::storage_usage();
+        let data_mut = self.data_mut();
+        let ret = data_mut.templates.insert(template_id, template).is_none();
+        data_mut.templates.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_template(&mut self, template_id: String) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().templates.remove(&template_id).is_some()
+    }
+
+    /// Add an account on the terms of a template, `total_amount` including the TGE share.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn add_account_from_template(
+        &mut self,
+        account_id: AccountId,
+        template_id: String,
+        total_amount: U128,
+        start: TimestampSec,
+    ) -> bool {
+        self.assert_owner();
+        self.assert_not_closed();
+        let prev_storage = env::storage_usage();
+        let template = self
+            .data()
+            .templates
+            .get(&template_id)
+            .cloned()
+            .expect("ERR_TEMPLATE_NOT_EXIST");
+        let tge_amount = total_amount.0 * template.tge_bps as u128 / BPS_DENOMINATOR as u128;
+        let options = AccountOptions {
+            interval_unit: template.interval_unit,
+            total_amount: Some(total_amount),
+            tge_amount: tge_amount.into(),
+            cliff: template.cliff,
+            category: template.category,
+            ..Default::default()
+        };
+        let ret = self.internal_add_account(
+            account_id,
+            start,
+            template.session_interval,
+            template.session_num,
+            None,
+            &options,
+        );
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    pub fn get_template(&self, template_id: String) -> Option<VestingTemplate> {
+        self.data().templates.get(&template_id).cloned()
+    }
+
+    pub fn get_templates(&self) -> Vec<TemplateInfo> {
+        self.data()
+            .templates
+            .iter()
+            .map(|(template_id, template)| TemplateInfo {
+                template_id: template_id.clone(),
+                template: template.clone(),
+            })
+            .collect()
+    }
+}
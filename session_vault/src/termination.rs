@@ -0,0 +1,162 @@
+//! Ending a grant early when its beneficiary leaves, by the policy chosen at `add_account`.
+//!
+//! A good leaver keeps the sessions vested so far plus the policy's acceleration, a bad
+//! leaver keeps the vested sessions or, if the policy says so, only the claimed ones.
+//! Kept sessions are unlocked at once, the rest is forfeited to the treasury.
+use crate::account::Account;
+use crate::events::emit_event;
+use crate::milestone::MilestoneStatus;
+use crate::staking::assert_nothing_staked;
+use crate::utils::TimestampSec;
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde_json::json;
+
+#[derive(Clone, Copy, Default)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TerminationPolicy {
+    // sessions (or locked milestones) a good leaver keeps on top of the vested ones
+    pub good_leaver_acceleration: u32,
+    // whether a bad leaver also forfeits the vested but unclaimed sessions
+    pub bad_leaver_forfeits_unclaimed: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum TerminationKind {
+    GoodLeaver,
+    BadLeaver,
+}
+
+/// Outcome of a termination, kept in the account.
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Termination {
+    pub kind: TerminationKind,
+    pub timestamp: TimestampSec,
+    // sessions the account keeps, all unlocked
+    pub kept_sessions: u32,
+    pub forfeited_amount: U128,
+}
+
+impl Account {
+    /// Apply the termination policy at `cur_ts`, returns the forfeited amount.
+    fn terminate(&mut self, kind: TerminationKind, cur_ts: u64) -> u128 {
+        assert!(self.termination.is_none(), "ERR_ACCOUNT_TERMINATED");
+        let policy = self.termination_policy;
+        let forfeited_amount = if self.milestones.is_empty() {
+            let vested = self.unlocked_sessions(cur_ts).min(self.session_num);
+            let kept_sessions = match kind {
+                TerminationKind::GoodLeaver => vested
+                    .saturating_add(policy.good_leaver_acceleration)
+                    .min(self.session_num),
+                TerminationKind::BadLeaver if policy.bad_leaver_forfeits_unclaimed => {
+                    self.last_claim_session
+                }
+                TerminationKind::BadLeaver => vested,
+            };
+            let forfeited_amount = self.total_amount() - self.released_amount(kept_sessions);
+            self.termination = Some(Termination {
+                kind,
+                timestamp: U64(cur_ts / 10u64.pow(9)),
+                kept_sessions,
+                forfeited_amount: forfeited_amount.into(),
+            });
+            forfeited_amount
+        } else {
+            let mut accelerated = match kind {
+                TerminationKind::GoodLeaver => policy.good_leaver_acceleration,
+                TerminationKind::BadLeaver => 0,
+            };
+            let mut forfeited_amount = 0;
+            for milestone in self.milestones.iter_mut() {
+                let forfeited = match milestone.status {
+                    MilestoneStatus::Locked if accelerated > 0 => {
+                        accelerated -= 1;
+                        milestone.status = MilestoneStatus::Released;
+                        false
+                    }
+                    MilestoneStatus::Locked => true,
+                    MilestoneStatus::Released => {
+                        kind == TerminationKind::BadLeaver && policy.bad_leaver_forfeits_unclaimed
+                    }
+                    _ => false,
+                };
+                if forfeited {
+                    milestone.status = MilestoneStatus::Reclaimed;
+                    forfeited_amount += milestone.amount.0;
+                }
+            }
+            self.termination = Some(Termination {
+                kind,
+                timestamp: U64(cur_ts / 10u64.pow(9)),
+                kept_sessions: self.unlocked_sessions(cur_ts),
+                forfeited_amount: forfeited_amount.into(),
+            });
+            forfeited_amount
+        };
+        if self.deposited_amount.0 > 0 {
+            self.deposited_amount = (self.deposited_amount.0 - forfeited_amount).into();
+        }
+        forfeited_amount
+    }
+}
+
+#[near]
+impl Contract {
+    /// Terminate the grant of a leaver by its termination policy.
+    /// If the account was funded, the forfeited amount is sent to the treasury.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn terminate_account(&mut self, account_id: AccountId, kind: TerminationKind) -> U128 {
+        self.assert_owner();
+        assert_one_yocto();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        // a swept account has nothing left to forfeit
+        assert!(!account.expired, "ERR_ACCOUNT_EXPIRED");
+        assert_nothing_staked(&account);
+        let funded = account.deposited_amount.0 > 0;
+        let forfeited_amount = account.terminate(kind, env::block_timestamp());
+        let termination = account.termination.clone().unwrap();
+        let asset = self.internal_account_asset(&account);
+        self.internal_deallocate_from_category(&account, forfeited_amount, funded);
+        self.internal_checkpoint_account(&account);
+        emit_event(
+            "account_terminated",
+            json!({
+                "account_id": account_id,
+                "kind": kind,
+                "kept_sessions": termination.kept_sessions,
+                "forfeited_amount": U128(forfeited_amount),
+            }),
+        );
+        self.data_mut()
+            .accounts
+            .insert(account_id.clone(), account.into());
+        if funded && forfeited_amount > 0 {
+            self.internal_update_balance(&asset, |balance| {
+                balance.total_balance = (balance.total_balance.0 - forfeited_amount).into();
+            });
+            // the promise is scheduled when dropped
+            let _ = self.internal_send_to_treasury(
+                asset,
+                forfeited_amount,
+                format!(
+                    "Forfeiting {} of terminated {} from {}",
+                    forfeited_amount,
+                    account_id,
+                    env::current_account_id()
+                ),
+            );
+        }
+        forfeited_amount.into()
+    }
+}
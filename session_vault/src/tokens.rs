@@ -0,0 +1,170 @@
+//! Tokens a vault holds grants in.
+//!
+//! The token given at init is the default one, the burn and merkle pools are in it.
+//! Owner can whitelist more, a grant then names its token in `AccountOptions`, and its
+//! deposits, claims and forfeits go in that token. Balances are kept per token.
+//! A grant can also be in native NEAR, see `native`.
+use crate::account::Account;
+use crate::utils::*;
+use crate::*;
+use near_sdk::{assert_one_yocto, log, NearToken, Promise};
+
+/// What a grant is paid in.
+#[derive(Clone, PartialEq)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum Asset {
+    Ft(AccountId),
+    Native,
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Asset::Ft(token_id) => write!(f, "{}", token_id),
+            Asset::Native => write!(f, "NEAR"),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TokenBalance {
+    // the total deposited amount
+    pub total_balance: U128,
+    // already claimed balance
+    pub claimed_balance: U128,
+    // claimed but not yet delivered, waiting for withdraw_pending
+    pub pending_balance: U128,
+    // failed to reach the treasury, waiting for withdraw_treasury_pending
+    pub treasury_pending: U128,
+}
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TokenInfo {
+    pub token_id: AccountId,
+    // whether new grants and deposits can use it
+    pub whitelisted: bool,
+    #[serde(flatten)]
+    pub balance: TokenBalance,
+}
+
+impl Contract {
+    /// What the grant is in, the default token unless it names another or is native.
+    pub(crate) fn internal_account_asset(&self, account: &Account) -> Asset {
+        if account.native {
+            Asset::Native
+        } else {
+            Asset::Ft(
+                account
+                    .token_id
+                    .clone()
+                    .unwrap_or_else(|| self.data().token_account_id.clone()),
+            )
+        }
+    }
+
+    pub(crate) fn assert_token_whitelisted(&self, token_id: &AccountId) {
+        assert!(self.data().tokens.contains(token_id), "ERR_ILLEGAL_TOKEN");
+    }
+
+    pub(crate) fn internal_balance(&self, asset: &Asset) -> TokenBalance {
+        match asset {
+            Asset::Native => self.data().native_balance.clone(),
+            Asset::Ft(token_id) => self
+                .data()
+                .token_balances
+                .get(token_id)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn internal_update_balance(
+        &mut self,
+        asset: &Asset,
+        f: impl FnOnce(&mut TokenBalance),
+    ) {
+        match asset {
+            Asset::Native => f(&mut self.data_mut().native_balance),
+            Asset::Ft(token_id) => {
+                let mut balance = self.internal_balance(asset);
+                f(&mut balance);
+                self.data_mut()
+                    .token_balances
+                    .insert(token_id.clone(), balance);
+            }
+        }
+    }
+
+    /// Send `amount` of `asset` out of the vault.
+    pub(crate) fn internal_transfer(
+        &self,
+        asset: &Asset,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: String,
+    ) -> Promise {
+        match asset {
+            Asset::Ft(token_id) => ext_fungible_token::ext(token_id.clone())
+                .with_attached_deposit(ONE_YOCTO)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver_id, amount.into(), Some(memo)),
+            Asset::Native => {
+                log!("{}", memo);
+                Promise::new(receiver_id).transfer(NearToken::from_yoctonear(amount))
+            }
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    /// Allow grants in another token. Only can be called by owner.
+    #[payable]
+    pub fn add_token(&mut self, token_id: AccountId) -> bool {
+        self.assert_owner();
+        self.assert_not_closed();
+        let prev_storage = env::storage_usage();
+        let ret = self.data_mut().tokens.insert(token_id.clone());
+        if !self.data().token_balances.contains_key(&token_id) {
+            self.data_mut()
+                .token_balances
+                .insert(token_id, TokenBalance::default());
+        }
+        let data_mut = self.data_mut();
+        data_mut.tokens.flush();
+        data_mut.token_balances.flush();
+        self.internal_check_storage(prev_storage);
+        ret
+    }
+
+    /// Stop new grants and deposits in a token, existing grants still claim in it.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn remove_token(&mut self, token_id: AccountId) -> bool {
+        self.assert_owner();
+        assert_one_yocto();
+        assert!(
+            token_id != self.data().token_account_id,
+            "ERR_CANNOT_REMOVE_DEFAULT_TOKEN"
+        );
+        self.data_mut().tokens.remove(&token_id)
+    }
+
+    /// Every token the vault has held grants in, with its balances.
+    pub fn get_tokens(&self) -> Vec<TokenInfo> {
+        self.data()
+            .token_balances
+            .iter()
+            .map(|(token_id, balance)| TokenInfo {
+                token_id: token_id.clone(),
+                whitelisted: self.data().tokens.contains(token_id),
+                balance: balance.clone(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,97 @@
+//! Moving a grant to another NEAR account, eg. to consolidate wallets.
+//! The beneficiary proposes, the new account accepts, and when the vault requires it
+//! the owner approves in between. The owner can also force it for lost keys.
+use crate::freeze::assert_not_frozen;
+use crate::*;
+use near_sdk::{assert_one_yocto, log};
+
+#[near]
+impl Contract {
+    /// Propose to move the caller's grant to `new_account_id`, or cancel with None.
+    #[payable]
+    pub fn propose_account_transfer(&mut self, new_account_id: Option<AccountId>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert_not_frozen(&account);
+        if let Some(new_account_id) = &new_account_id {
+            assert!(
+                !self.data().accounts.contains_key(new_account_id),
+                "ERR_ACCOUNT_ALREADY_EXIST"
+            );
+        }
+        account.transfer_to = new_account_id;
+        account.transfer_approved = false;
+        log!(
+            "{} proposed transfer to {:?}",
+            account_id,
+            account.transfer_to
+        );
+        self.data_mut().accounts.insert(account_id, account.into());
+    }
+
+    /// Approve a proposed transfer. Only can be called by owner.
+    #[payable]
+    pub fn approve_account_transfer(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert_one_yocto();
+        let mut account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(account.transfer_to.is_some(), "ERR_NO_TRANSFER_PROPOSED");
+        account.transfer_approved = true;
+        self.data_mut().accounts.insert(account_id, account.into());
+    }
+
+    /// Called by the proposed new account to take over the grant of `account_id`.
+    #[payable]
+    pub fn accept_account_transfer(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        let new_account_id = env::predecessor_account_id();
+        let account = self
+            .data()
+            .accounts
+            .get(&account_id)
+            .map(|va| va.clone().into_current())
+            .expect("ERR_ACCOUNT_NOT_EXIST");
+        assert!(
+            account.transfer_to.as_ref() == Some(&new_account_id),
+            "ERR_TRANSFER_NOT_PROPOSED"
+        );
+        assert!(
+            account.transfer_approved || !self.data().transfer_requires_approval,
+            "ERR_TRANSFER_NOT_APPROVED"
+        );
+        self.internal_rekey_account(&account_id, &new_account_id);
+        log!("Account {} transferred to {}", account_id, new_account_id);
+    }
+
+    /// Move a grant without its holder, for lost keys. Only can be called by owner.
+    #[payable]
+    pub fn force_transfer_account(&mut self, account_id: AccountId, new_account_id: AccountId) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.internal_rekey_account(&account_id, &new_account_id);
+        log!(
+            "Account {} transferred to {} by owner",
+            account_id,
+            new_account_id
+        );
+    }
+
+    /// Whether transfers need owner approval. Only can be called by owner.
+    #[payable]
+    pub fn set_transfer_requires_approval(&mut self, required: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().transfer_requires_approval = required;
+    }
+}
@@ -0,0 +1,88 @@
+//! Where tokens taken back from grants go.
+//!
+//! The treasury is the owner unless set otherwise. A transfer to it that fails is
+//! kept in the vault as treasury pending of its asset, and can be sent again by owner.
+use crate::utils::*;
+use crate::*;
+use near_sdk::{assert_one_yocto, is_promise_success, log, Promise, PromiseOrValue};
+
+impl Contract {
+    pub(crate) fn internal_treasury_id(&self) -> AccountId {
+        self.data()
+            .treasury_id
+            .clone()
+            .unwrap_or_else(|| self.data().owner_id.clone())
+    }
+
+    /// Send `amount` of `asset`, already taken out of the grants, to the treasury.
+    pub(crate) fn internal_send_to_treasury(
+        &self,
+        asset: Asset,
+        amount: u128,
+        memo: String,
+    ) -> Promise {
+        self.internal_transfer(&asset, self.internal_treasury_id(), amount, memo)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_treasury_transfer(asset, amount.into()),
+            )
+    }
+}
+
+#[near]
+impl Contract {
+    /// Set (or reset to owner with None) the treasury. Only can be called by owner.
+    #[payable]
+    pub fn set_treasury(&mut self, treasury_id: Option<AccountId>) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().treasury_id = treasury_id;
+    }
+
+    pub fn get_treasury(&self) -> AccountId {
+        self.internal_treasury_id()
+    }
+
+    /// Send again what failed to reach the treasury, in the default token unless
+    /// `asset` is given. Only can be called by owner.
+    #[payable]
+    pub fn withdraw_treasury_pending(&mut self, asset: Option<Asset>) -> PromiseOrValue<bool> {
+        self.assert_owner();
+        assert_one_yocto();
+        let asset = asset.unwrap_or_else(|| Asset::Ft(self.data().token_account_id.clone()));
+        let amount = self.internal_balance(&asset).treasury_pending.0;
+        if amount == 0 {
+            return PromiseOrValue::Value(false);
+        }
+        self.internal_update_balance(&asset, |balance| {
+            balance.treasury_pending = 0.into();
+        });
+        PromiseOrValue::Promise(self.internal_send_to_treasury(
+            asset,
+            amount,
+            format!(
+                "Withdrawing treasury pending {} from {}",
+                amount,
+                env::current_account_id()
+            ),
+        ))
+    }
+
+    #[private]
+    pub fn after_treasury_transfer(&mut self, asset: Asset, amount: U128) -> bool {
+        let promise_success = is_promise_success();
+        if !promise_success {
+            self.internal_update_balance(&asset, |balance| {
+                balance.treasury_pending = (balance.treasury_pending.0 + amount.0).into();
+            });
+            log!(
+                "Treasury transfer of {} failed and moved to treasury pending, balance is {}",
+                asset,
+                amount.0
+            );
+        }
+        promise_success
+    }
+}
@@ -1,14 +1,52 @@
 use near_sdk::{
-    ext_contract,
+    env, ext_contract,
     json_types::{U128, U64},
     AccountId, Gas, NearToken, Timestamp,
 };
 
+use std::convert::TryInto;
+
 pub type TimestampSec = U64;
 
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas::from_gas(10_000_000_000_000);
 #[allow(unused)]
 pub const GAS_FOR_AFTER_FT_TRANSFER: Gas = Gas::from_gas(10_000_000_000_000);
+pub const GAS_FOR_FT_BALANCE_OF: Gas = Gas::from_gas(5_000_000_000_000);
+/// Sends the balance to the treasury, with its own callback.
+pub const GAS_FOR_AFTER_CLOSE_VAULT: Gas = Gas::from_gas(30_000_000_000_000);
+pub const GAS_FOR_MIGRATE_CALL: Gas = Gas::from_gas(50_000_000_000_000);
+pub const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas::from_gas(25_000_000_000_000);
+pub const GAS_FOR_NFT_RESOLVE_TRANSFER: Gas = Gas::from_gas(10_000_000_000_000);
+/// ft_transfer_call to the staking contract, its ft_on_transfer and ft_resolve_transfer.
+pub const GAS_FOR_STAKE: Gas = Gas::from_gas(60_000_000_000_000);
+pub const GAS_FOR_AFTER_STAKE: Gas = Gas::from_gas(10_000_000_000_000);
+/// unstake of the staking contract, with its ft_transfer_call back to the vault.
+pub const GAS_FOR_UNSTAKE: Gas = Gas::from_gas(80_000_000_000_000);
+
+/// `ft_transfer_call` msg that funds the burn pool instead of an account.
+/// Upper case can never be a valid account ID.
+pub const BURN_POOL_MSG: &str = "BURN_POOL";
+/// `ft_transfer_call` msg that funds the merkle allocations pool.
+pub const MERKLE_POOL_MSG: &str = "MERKLE_POOL";
+/// Prefix of the `ft_transfer_call` msg a staking contract sends staked tokens back with,
+/// followed by the account ID.
+pub const UNSTAKE_MSG_PREFIX: &str = "UNSTAKE:";
+
+pub const MAX_CLAIM_DELEGATES: usize = 10;
+
+pub const MAX_MILESTONES: usize = 20;
+
+/// Fractions are given in basis points.
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Each distributed account costs a transfer and its callback.
+pub const MAX_DISTRIBUTE_LIMIT: u32 = 10;
+
+/// Accounts rewritten per accelerate call.
+pub const MAX_ACCELERATE_LIMIT: u32 = 50;
+
+/// Accounts checked per check_settled call.
+pub const MAX_CHECK_SETTLED_LIMIT: u32 = 100;
 
 pub const ONE_YOCTO: NearToken = NearToken::from_yoctonear(1);
 #[allow(unused)]
@@ -18,6 +56,70 @@ pub(crate) fn to_nano(timestamp: TimestampSec) -> Timestamp {
     Timestamp::from(timestamp) * 10u64.pow(9)
 }
 
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Days since 1970-01-01 of a proleptic Gregorian UTC date (H. Hinnant's algorithm).
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// UTC date `(year, month, day)` of the given days since 1970-01-01.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 {
+        yoe + era * 400 + 1
+    } else {
+        yoe + era * 400
+    };
+    (year, month, day)
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+/// Move a timestamp (in seconds) by whole calendar months, keeping the UTC time of day.
+/// The day is clamped to the end of shorter months, eg: Jan 31 + 1 month is Feb 28 (or 29).
+pub(crate) fn add_months(timestamp: u64, months: u64) -> u64 {
+    let (year, month, day) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    let total = year * 12 + (month - 1) + months;
+    let (year, month) = (total / 12, total % 12 + 1);
+    let day = day.min(days_in_month(year, month));
+    days_from_civil(year, month, day) * SECONDS_PER_DAY + timestamp % SECONDS_PER_DAY
+}
+
+/// How many whole calendar months have passed from `from` to `to` (in seconds).
+pub(crate) fn months_between(from: u64, to: u64) -> u64 {
+    if to <= from {
+        return 0;
+    }
+    let (from_year, from_month, _) = civil_from_days(from / SECONDS_PER_DAY);
+    let (to_year, to_month, _) = civil_from_days(to / SECONDS_PER_DAY);
+    let months = (to_year * 12 + to_month) - (from_year * 12 + from_month);
+    if add_months(from, months) > to {
+        months - 1
+    } else {
+        months
+    }
+}
+
 #[allow(unused)]
 #[ext_contract(ext_self)]
 trait AccountClaimCallbacks {
@@ -28,4 +130,50 @@ trait AccountClaimCallbacks {
 #[ext_contract(ext_fungible_token)]
 trait ExtFungibleToken {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> U128;
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+}
+
+/// What a whitelisted staking contract implements besides ft_on_transfer,
+/// which takes the stake with the account ID as msg.
+#[allow(unused)]
+#[ext_contract(ext_staking_contract)]
+trait ExtStakingContract {
+    /// Send `amount` staked for `account_id` back to the vault with its rewards, in one
+    /// `ft_transfer_call` with msg `UNSTAKE:<account_id>`.
+    fn unstake(&mut self, account_id: AccountId, amount: U128);
+}
+
+pub(crate) type EvmAddress = [u8; 20];
+
+/// Parse a `0x` prefixed, 20 bytes hex EVM address.
+pub(crate) fn parse_evm_address(address: &str) -> EvmAddress {
+    let bytes = hex::decode(address.strip_prefix("0x").expect("ERR_INVALID_EVM_ADDRESS"))
+        .expect("ERR_INVALID_EVM_ADDRESS");
+    bytes.try_into().expect("ERR_INVALID_EVM_ADDRESS")
+}
+
+/// Recover the EVM address that produced an EIP-191 `personal_sign` signature
+/// (65 bytes hex `r || s || v`) over `message`.
+pub(crate) fn recover_evm_signer(message: &[u8], signature: &str) -> EvmAddress {
+    let signature = hex::decode(signature.strip_prefix("0x").unwrap_or(signature))
+        .expect("ERR_INVALID_SIGNATURE");
+    assert!(signature.len() == 65, "ERR_INVALID_SIGNATURE_LENGTH");
+    let v = match signature[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        _ => env::panic_str("ERR_INVALID_SIGNATURE"),
+    };
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    let hash = env::keccak256_array(&prefixed);
+    let public_key =
+        env::ecrecover(&hash, &signature[..64], v, true).expect("ERR_INVALID_SIGNATURE");
+    env::keccak256_array(&public_key)[12..].try_into().unwrap()
 }
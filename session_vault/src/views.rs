@@ -1,5 +1,6 @@
 use crate::utils::*;
 use crate::*;
+use near_sdk::PublicKey;
 
 #[derive(Clone)]
 #[near(serializers = [json])]
@@ -8,12 +9,42 @@ pub struct ContractInfo {
     pub version: String,
     // only onwer can manage accounts
     pub owner_id: AccountId,
-    // token kept by this vault
+    // default token of grants, and the one of burn and merkle pools
     pub token_account_id: AccountId,
-    // the total deposited amount in this vault
+    // the total deposited amount in the default token
     pub total_balance: U128,
-    // already claimed balance
+    // already claimed balance in the default token
     pub claimed_balance: U128,
+    // balance available to grants created from burns
+    pub burn_pool: U128,
+    // balance available to merkle allocations not yet activated
+    pub merkle_pool: U128,
+    // claimed but not yet delivered in the default token, waiting for withdraw_pending
+    pub pending_balance: U128,
+    // whether account transfers need owner approval
+    pub transfer_requires_approval: bool,
+    // receives tokens taken back from grants
+    pub treasury_id: AccountId,
+    // failed to reach the treasury in the default token, waiting for withdraw_treasury_pending
+    pub treasury_pending: U128,
+    // claim deadline of accounts without their own
+    pub claim_deadline: Option<TimestampSec>,
+    // wound down by close_vault
+    pub closed: bool,
+    // whether deposits record their sender on the account
+    pub record_funders: bool,
+    // balances of every token, the default one included
+    pub tokens: Vec<TokenInfo>,
+    // balances of grants in native NEAR
+    pub native_balance: TokenBalance,
+    // factory that deployed this vault, allowed to upgrade it
+    pub factory_id: Option<AccountId>,
+    // whether new grants are minted a NEP-171 token
+    pub nft_enabled: bool,
+    // whether what accounts hold is recorded by block height
+    pub voting_checkpoints: bool,
+    // whether beneficiaries can stake their own grants
+    pub beneficiary_staking: bool,
 }
 
 #[near(serializers=[json])]
@@ -37,29 +68,123 @@ pub struct AccountInfo {
     pub session_num: u32,
     // the session index of previous claim, start from 1
     pub last_claim_session: u32,
-    // expected total_amount = session_num * release_per_session
     pub release_per_session: U128,
+    // expected total amount of the round, including the remainder
+    pub total_amount: U128,
+    // how the part of total_amount not divisible by session_num is released
+    pub remainder_policy: RemainderPolicy,
 
     pub claimed_amount: U128,
     pub deposited_amount: U128,
 
     pub unclaimed_amount: U128,
+
+    pub claim_policy: ClaimPolicy,
+    pub claim_public_key: Option<PublicKey>,
+    // next signed claim must use a greater nonce
+    pub claim_nonce: U64,
+    // claimed but not delivered, can be pulled with withdraw_pending
+    pub pending_amount: U128,
+    pub last_claim_error: Option<String>,
+    // sent by a claim not resolved yet, the grant can't change hands meanwhile
+    pub in_flight_amount: U128,
+    // proposed new holder of this grant
+    pub transfer_to: Option<AccountId>,
+    pub transfer_approved: bool,
+    pub interval_unit: IntervalUnit,
+    // when the next session unlocks, None once all sessions are unlocked or for milestones
+    pub next_unlock_timestamp: Option<TimestampSec>,
+    // when the last session unlocks
+    pub end_timestamp: TimestampSec,
+    // sessions released by attesters, empty for time based accounts
+    pub milestones: Vec<Milestone>,
+    pub termination_policy: TerminationPolicy,
+    // outcome of terminate_account, None while active
+    pub termination: Option<Termination>,
+    // sessions unlocked ahead of the schedule by accelerate
+    pub accelerated_sessions: u32,
+    // no claim after it, the one of the account or of the vault
+    pub claim_deadline: Option<TimestampSec>,
+    // swept after the claim deadline
+    pub expired: bool,
+    // who deposited the current round, if recorded
+    pub funder_id: Option<AccountId>,
+    // token of the grant, None if native
+    pub token_id: Option<AccountId>,
+    // in native NEAR
+    pub native: bool,
+    // released by the first session at start
+    pub tge_amount: U128,
+    // in interval_unit, no vesting session unlocks before start + cliff
+    pub cliff: TimestampSec,
+    // tokenomics category
+    pub category: Option<String>,
+    // NEP-171 token representing the grant
+    pub nft_token_id: Option<String>,
+    pub nft_transfer_disabled: bool,
+    // contract part of the locked balance is staked in
+    pub staking_id: Option<AccountId>,
+    // locked but staked out of the vault, can't be claimed until unstaked
+    pub staked_amount: U128,
+    // earned by staking, can be pulled with withdraw_staking_rewards
+    pub staking_rewards: U128,
+    // compliance hold, None unless frozen
+    pub freeze: Option<Freeze>,
 }
 
 impl From<VAccount> for AccountInfo {
     fn from(vacc: VAccount) -> Self {
-        match vacc {
-            VAccount::Current(acc) => Self {
-                account_id: acc.account_id.clone(),
-                start_timestamp: acc.start_timestamp,
-                session_interval: acc.session_interval,
-                session_num: acc.session_num,
-                last_claim_session: acc.last_claim_session,
-                release_per_session: acc.release_per_session,
-                claimed_amount: acc.claimed_amount,
-                deposited_amount: acc.deposited_amount,
-                unclaimed_amount: acc.unclaimed_amount(env::block_timestamp()).into(),
-            },
+        let acc = vacc.into_current();
+        let cur_ts = env::block_timestamp();
+        let next_session = acc.unlocked_sessions(cur_ts) + 1;
+        let next_unlock_timestamp = if acc.milestones.is_empty() && next_session <= acc.session_num
+        {
+            Some(U64(acc.session_timestamp(next_session)))
+        } else {
+            None
+        };
+        let end_timestamp = U64(acc.session_timestamp(acc.session_num));
+        Self {
+            account_id: acc.account_id.clone(),
+            start_timestamp: acc.start_timestamp,
+            session_interval: acc.session_interval,
+            session_num: acc.session_num,
+            last_claim_session: acc.last_claim_session,
+            release_per_session: acc.release_per_session,
+            total_amount: acc.total_amount().into(),
+            remainder_policy: acc.remainder_policy,
+            claimed_amount: acc.claimed_amount,
+            deposited_amount: acc.deposited_amount,
+            unclaimed_amount: acc.unclaimed_amount(cur_ts).into(),
+            claim_policy: acc.claim_policy,
+            claim_public_key: acc.claim_public_key,
+            claim_nonce: acc.claim_nonce,
+            pending_amount: acc.pending_amount,
+            last_claim_error: acc.last_claim_error,
+            in_flight_amount: acc.in_flight_amount,
+            transfer_to: acc.transfer_to,
+            transfer_approved: acc.transfer_approved,
+            interval_unit: acc.interval_unit,
+            next_unlock_timestamp,
+            end_timestamp,
+            milestones: acc.milestones,
+            termination_policy: acc.termination_policy,
+            termination: acc.termination,
+            accelerated_sessions: acc.accelerated_sessions,
+            claim_deadline: acc.claim_deadline,
+            expired: acc.expired,
+            funder_id: acc.funder_id,
+            token_id: acc.token_id,
+            native: acc.native,
+            tge_amount: acc.tge_amount,
+            cliff: acc.cliff,
+            category: acc.category,
+            nft_token_id: acc.nft_token_id,
+            nft_transfer_disabled: acc.nft_transfer_disabled,
+            staking_id: acc.staking_id,
+            staked_amount: acc.staked_amount,
+            staking_rewards: acc.staking_rewards,
+            freeze: acc.freeze,
         }
     }
 }
@@ -70,17 +195,47 @@ impl From<&VAccount> for AccountInfo {
     }
 }
 
+impl Contract {
+    pub(crate) fn internal_account_info(&self, vacc: &VAccount) -> AccountInfo {
+        let mut account_info: AccountInfo = vacc.into();
+        account_info.claim_deadline = account_info.claim_deadline.or(self.data().claim_deadline);
+        if !account_info.native {
+            account_info.token_id = account_info
+                .token_id
+                .or_else(|| Some(self.data().token_account_id.clone()));
+        }
+        account_info
+    }
+}
+
 #[near]
 impl Contract {
     /// Return contract basic info
     pub fn contract_metadata(&self) -> ContractInfo {
         let current_state = self.data();
+        let default_balance =
+            self.internal_balance(&Asset::Ft(current_state.token_account_id.clone()));
         ContractInfo {
             version: env!("CARGO_PKG_VERSION").to_string(),
             owner_id: current_state.owner_id.clone(),
             token_account_id: current_state.token_account_id.clone(),
-            total_balance: current_state.total_balance,
-            claimed_balance: current_state.claimed_balance,
+            total_balance: default_balance.total_balance,
+            claimed_balance: default_balance.claimed_balance,
+            burn_pool: current_state.burn_pool,
+            merkle_pool: current_state.merkle_pool,
+            pending_balance: default_balance.pending_balance,
+            transfer_requires_approval: current_state.transfer_requires_approval,
+            treasury_id: self.internal_treasury_id(),
+            treasury_pending: default_balance.treasury_pending,
+            claim_deadline: current_state.claim_deadline,
+            closed: current_state.closed,
+            record_funders: current_state.record_funders,
+            tokens: self.get_tokens(),
+            native_balance: current_state.native_balance.clone(),
+            factory_id: current_state.factory_id.clone(),
+            nft_enabled: current_state.nft_enabled,
+            voting_checkpoints: current_state.voting_checkpoints,
+            beneficiary_staking: current_state.beneficiary_staking,
         }
     }
 
@@ -97,8 +252,16 @@ impl Contract {
         }
     }
 
+    /// Accounts besides owner allowed to fund accounts.
+    pub fn get_funders(&self) -> Vec<AccountId> {
+        self.data().funders.iter().cloned().collect()
+    }
+
     pub fn get_account(&self, account_id: AccountId) -> Option<AccountInfo> {
-        self.data().accounts.get(&account_id).map(Into::into)
+        self.data()
+            .accounts
+            .get(&account_id)
+            .map(|vacc| self.internal_account_info(vacc))
     }
 
     pub fn list_accounts(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountInfo> {
@@ -112,7 +275,7 @@ impl Contract {
             Some(account_id) => {
                 // Will always be Some
                 let account_info = self.data().accounts.get(account_id).unwrap();
-                account_infos.push(account_info.into());
+                account_infos.push(self.internal_account_info(account_info));
             }
         }
         let keys = keys.enumerate();
@@ -121,7 +284,7 @@ impl Contract {
                 break;
             }
             let account_info = self.data().accounts.get(account).unwrap();
-            account_infos.push(account_info.into());
+            account_infos.push(self.internal_account_info(account_info));
         }
         account_infos
     }
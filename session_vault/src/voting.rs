@@ -0,0 +1,162 @@
+//! Voting power of the tokens held in the vault, for governance.
+//!
+//! The vault is the holder of record, so snapshot tools reading token balances see
+//! nothing of the grants on their beneficiaries. The views here report what each
+//! account holds in the vault, in the default token only. When owner enables
+//! checkpoints, the amount held is also recorded by block height at each change,
+//! so that a governance contract can query past power.
+use crate::account::Account;
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+#[derive(Clone)]
+#[near(serializers = [json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct VotingPower {
+    // deposited, not unlocked yet
+    pub locked: U128,
+    // unlocked, not claimed yet
+    pub unclaimed: U128,
+}
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Checkpoint {
+    pub block_height: U64,
+    // held in the vault from this block on
+    pub amount: U128,
+}
+
+/// Only grants in the default token count.
+fn is_voting_account(account: &Account) -> bool {
+    account.token_id.is_none() && !account.native
+}
+
+impl Contract {
+    fn internal_voting_power(&self, account: &Account, cur_ts: u64) -> VotingPower {
+        if !is_voting_account(account) || self.internal_is_claim_expired(account, cur_ts) {
+            return VotingPower {
+                locked: 0.into(),
+                unclaimed: 0.into(),
+            };
+        }
+        // an unfunded grant unlocks what it doesn't hold
+        let locking = account.locking_amount().0;
+        let unclaimed = account.unclaimed_amount(cur_ts).min(locking);
+        VotingPower {
+            locked: (locking - unclaimed).into(),
+            unclaimed: unclaimed.into(),
+        }
+    }
+
+    /// Record `amount` held by `account_id` at the current block, if enabled.
+    fn internal_checkpoint(&mut self, account_id: &AccountId, amount: u128) {
+        if !self.data().voting_checkpoints {
+            return;
+        }
+        let block_height = env::block_height();
+        let mut checkpoints = self
+            .data()
+            .checkpoints
+            .get(account_id)
+            .cloned()
+            .unwrap_or_default();
+        match checkpoints.last_mut() {
+            Some(last) if last.block_height.0 == block_height => last.amount = amount.into(),
+            Some(last) if last.amount.0 == amount => return,
+            _ => checkpoints.push(Checkpoint {
+                block_height: block_height.into(),
+                amount: amount.into(),
+            }),
+        }
+        let data_mut = self.data_mut();
+        data_mut.checkpoints.insert(account_id.clone(), checkpoints);
+        data_mut.checkpoints.flush();
+        self.assert_native_covered();
+    }
+
+    /// Record what the account holds after a deposit, claim or take back.
+    pub(crate) fn internal_checkpoint_account(&mut self, account: &Account) {
+        if is_voting_account(account) {
+            self.internal_checkpoint(&account.account_id, account.locking_amount().0);
+        }
+    }
+
+    /// Record a grant moved from `old_account_id` to its new holder.
+    pub(crate) fn internal_checkpoint_move(
+        &mut self,
+        account: &Account,
+        old_account_id: &AccountId,
+    ) {
+        if is_voting_account(account) {
+            self.internal_checkpoint(old_account_id, 0);
+            self.internal_checkpoint_account(account);
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    /// Start (or stop) recording checkpoints. Accounts get their first one at
+    /// their next change. Only can be called by owner.
+    #[payable]
+    pub fn set_voting_checkpoints(&mut self, enabled: bool) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.data_mut().voting_checkpoints = enabled;
+    }
+
+    /// Locked and unclaimed amounts the account holds in the vault,
+    /// zero for grants in other tokens and after the claim deadline.
+    pub fn get_voting_power(&self, account_id: AccountId) -> VotingPower {
+        let cur_ts = env::block_timestamp();
+        self.data()
+            .accounts
+            .get(&account_id)
+            .map(|va| self.internal_voting_power(&va.clone().into_current(), cur_ts))
+            .unwrap_or(VotingPower {
+                locked: 0.into(),
+                unclaimed: 0.into(),
+            })
+    }
+
+    /// (account, locked + unclaimed) pairs of a page of accounts, those holding nothing
+    /// left out, so a page can be shorter than `limit`.
+    pub fn get_voting_snapshot(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(AccountId, U128)> {
+        let cur_ts = env::block_timestamp();
+        let from_index = from_index.unwrap_or(U64(0)).0 as usize;
+        let limit = limit.unwrap_or(U64(self.data().accounts.len() as u64)).0 as usize;
+        self.data()
+            .accounts
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .filter_map(|(account_id, va)| {
+                let power = self.internal_voting_power(&va.clone().into_current(), cur_ts);
+                let amount = power.locked.0 + power.unclaimed.0;
+                (amount > 0).then(|| (account_id.clone(), amount.into()))
+            })
+            .collect()
+    }
+
+    /// What the account held in the vault at `block_height`,
+    /// None if it has no checkpoint at or before it.
+    pub fn get_locked_at(&self, account_id: AccountId, block_height: U64) -> Option<U128> {
+        let checkpoints = self.data().checkpoints.get(&account_id)?;
+        let index = checkpoints.partition_point(|c| c.block_height.0 <= block_height.0);
+        index.checked_sub(1).map(|index| checkpoints[index].amount)
+    }
+
+    pub fn get_checkpoints(&self, account_id: AccountId) -> Vec<Checkpoint> {
+        self.data()
+            .checkpoints
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
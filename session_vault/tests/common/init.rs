@@ -65,6 +65,7 @@ use tokio::sync::OnceCell;
 
 static TEST_TOKEN_CONTRACT_WASM: OnceCell<Vec<u8>> = OnceCell::const_new();
 static SESSION_VAULT_CONTRACT_WASM: OnceCell<Vec<u8>> = OnceCell::const_new();
+static MOCK_STAKING_CONTRACT_WASM: OnceCell<Vec<u8>> = OnceCell::const_new();
 
 async fn test_token_contract_wasm() -> Vec<u8> {
     // let artifact: cargo_near_build::BuildArtifact = cargo_near_build::build(BuildOpts {
@@ -318,3 +319,87 @@ pub async fn setup_vault() -> (Worker<Sandbox>, Account, Contract, Contract) {
 
     (root, owner, vault, token)
 }
+
+/// Another session vault on the same token, eg. standing in for a staking contract.
+pub async fn deploy_session_vault(
+    root_account: &Account,
+    vault_id: &str,
+    owner_id: &AccountId,
+    token_id: &AccountId,
+) -> Contract {
+    let defi_contract_bytes = SESSION_VAULT_CONTRACT_WASM
+        .get_or_init(session_vault_contract_wasm)
+        .await;
+    let vault = root_account
+        .create_subaccount(vault_id)
+        .initial_balance(NearToken::from_near(20))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let vault = vault.deploy(defi_contract_bytes).await.unwrap().result;
+    let res = vault
+        .call("new")
+        .args_json((owner_id.to_string(), token_id.to_string()))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Failure logs is: {:?}", res.failures());
+    let res = root_account
+        .call(token_id, "storage_deposit")
+        .args_json((Some(vault.id()), Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    vault
+}
+
+async fn mock_staking_contract_wasm() -> Vec<u8> {
+    std::fs::read("../res/mock_staking.wasm").unwrap_or_else(|err| {
+        panic!(
+            "Could not read mock staking WASM file from {}\nErr: {err}",
+            "../res/mock_staking.wasm"
+        )
+    })
+}
+
+/// Deploys a mock staking contract paying `reward_percent` of what is unstaked as rewards,
+/// registered with the token.
+pub async fn deploy_mock_staking(
+    root_account: &Account,
+    staking_id: &str,
+    token_id: &AccountId,
+    reward_percent: u8,
+) -> Contract {
+    let staking_contract_bytes = MOCK_STAKING_CONTRACT_WASM
+        .get_or_init(mock_staking_contract_wasm)
+        .await;
+    let staking = root_account
+        .create_subaccount(staking_id)
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let staking = staking.deploy(staking_contract_bytes).await.unwrap().result;
+    let res = staking
+        .call("new")
+        .args_json((token_id.to_string(), reward_percent))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Failure logs is: {:?}", res.failures());
+    let res = root_account
+        .call(token_id, "storage_deposit")
+        .args_json((Some(staking.id()), Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    staking
+}
@@ -17,6 +17,25 @@ pub struct ContractInfo {
     pub total_balance: U128,
     // already claimed balance
     pub claimed_balance: U128,
+    // balance available to grants created from burns
+    pub burn_pool: U128,
+    // balance available to merkle allocations not yet activated
+    pub merkle_pool: U128,
+    // claimed but not yet delivered, waiting for withdraw_pending
+    pub pending_balance: U128,
+    // whether account transfers need owner approval
+    pub transfer_requires_approval: bool,
+    pub treasury_id: AccountId,
+    pub treasury_pending: U128,
+    pub claim_deadline: Option<U64>,
+    pub closed: bool,
+    pub record_funders: bool,
+    pub tokens: Vec<TokenInfo>,
+    pub native_balance: TokenBalance,
+    pub factory_id: Option<AccountId>,
+    pub nft_enabled: bool,
+    pub voting_checkpoints: bool,
+    pub beneficiary_staking: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -33,11 +52,202 @@ pub struct AccountInfo {
     pub session_num: u32,
     // the session index of previous claim, start from 1
     pub last_claim_session: u32,
-    // expected total_amount = session_num * release_per_session
     pub release_per_session: U128,
+    // expected total amount, including the remainder
+    pub total_amount: U128,
+    pub remainder_policy: RemainderPolicy,
 
     pub claimed_amount: U128,
     pub deposited_amount: U128,
     // unclaimed amount
     pub unclaimed_amount: U128,
+    // claimed but not delivered, can be pulled with withdraw_pending
+    pub pending_amount: U128,
+    pub last_claim_error: Option<String>,
+    pub in_flight_amount: U128,
+    // proposed new holder of this grant
+    pub transfer_to: Option<AccountId>,
+    pub transfer_approved: bool,
+    pub interval_unit: IntervalUnit,
+    // when the next session unlocks
+    pub next_unlock_timestamp: Option<U64>,
+    // when the last session unlocks
+    pub end_timestamp: U64,
+    pub milestones: Vec<Milestone>,
+    pub termination_policy: TerminationPolicy,
+    pub termination: Option<Termination>,
+    pub accelerated_sessions: u32,
+    pub claim_deadline: Option<U64>,
+    pub expired: bool,
+    pub funder_id: Option<AccountId>,
+    pub token_id: Option<AccountId>,
+    pub native: bool,
+    pub tge_amount: U128,
+    pub cliff: U64,
+    pub category: Option<String>,
+    pub nft_token_id: Option<String>,
+    pub nft_transfer_disabled: bool,
+    pub staking_id: Option<AccountId>,
+    pub staked_amount: U128,
+    pub staking_rewards: U128,
+    pub freeze: Option<Freeze>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum IntervalUnit {
+    #[default]
+    Second,
+    Month,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum RemainderPolicy {
+    #[default]
+    LastSession,
+    Spread,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountOptions {
+    pub interval_unit: IntervalUnit,
+    pub total_amount: Option<U128>,
+    pub remainder_policy: RemainderPolicy,
+    pub termination_policy: TerminationPolicy,
+    pub claim_deadline: Option<U64>,
+    pub token_id: Option<AccountId>,
+    pub native: bool,
+    pub tge_amount: U128,
+    pub cliff: U64,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneConfig {
+    pub description: String,
+    pub amount: U128,
+    pub deadline: Option<U64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum MilestoneStatus {
+    Locked,
+    Released,
+    Claimed,
+    Reclaimed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Milestone {
+    pub description: String,
+    pub amount: U128,
+    pub deadline: Option<U64>,
+    pub status: MilestoneStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TerminationPolicy {
+    pub good_leaver_acceleration: u32,
+    pub bad_leaver_forfeits_unclaimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub enum TerminationKind {
+    GoodLeaver,
+    BadLeaver,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Termination {
+    pub kind: TerminationKind,
+    pub timestamp: U64,
+    pub kept_sessions: u32,
+    pub forfeited_amount: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TokenInfo {
+    pub token_id: AccountId,
+    pub whitelisted: bool,
+    pub total_balance: U128,
+    pub claimed_balance: U128,
+    pub pending_balance: U128,
+    pub treasury_pending: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct TokenBalance {
+    pub total_balance: U128,
+    pub claimed_balance: U128,
+    pub pending_balance: U128,
+    pub treasury_pending: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct VestingTemplate {
+    pub cliff: U64,
+    pub session_interval: U64,
+    pub session_num: u32,
+    pub tge_bps: u32,
+    pub interval_unit: IntervalUnit,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct CategoryInfo {
+    pub category: String,
+    pub cap: U128,
+    pub allocated: U128,
+    pub deposited: U128,
+    pub claimed: U128,
+    pub nft_transfer_disabled: bool,
+    pub locked: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct VotingPower {
+    pub locked: U128,
+    pub unclaimed: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Checkpoint {
+    pub block_height: U64,
+    pub amount: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Freeze {
+    pub reason_code: String,
+    pub frozen_by: AccountId,
+    pub timestamp: U64,
 }
@@ -1,7 +1,8 @@
 use std::str::FromStr;
 
 use crate::common::{init::*, types::*};
-use common::utils::wait_seconds;
+use common::utils::{error_contains, wait_seconds};
+use near_contract_standards::non_fungible_token::Token;
 use near_sdk::{
     json_types::{U128, U64},
     AccountId, NearToken,
@@ -63,7 +64,14 @@ async fn sim_set_owner() {
 
     let res = user1
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(10), U64(10), 1, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -81,7 +89,14 @@ async fn sim_set_owner() {
 
     let outcome = user1
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(10), U64(10), 1, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -122,7 +137,14 @@ async fn sim_add_user() {
     assert!(res.is_success(), "Failure logs is: {:?}", res.failures());
     let res = user1
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(10), U64(10), 1, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -146,7 +168,14 @@ async fn sim_add_user() {
 
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(timestamp + 1), U64(1), 2, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(timestamp + 1),
+            U64(1),
+            2,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -159,7 +188,14 @@ async fn sim_add_user() {
     println!("Current Timestamp in seconds is {timestamp}");
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(timestamp), U64(1), 1, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(timestamp),
+            U64(1),
+            1,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -183,7 +219,14 @@ async fn sim_add_user() {
     println!("Current Timestamp in seconds is {timestamp}");
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(10), U64(10), 1, U128::from(100)))
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128::from(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -242,7 +285,14 @@ async fn sim_deposit_token() {
 
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(10), U64(10), 1, U128(100)))
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -484,6 +534,7 @@ async fn sim_claim() {
             U64(2),
             1,
             U128(100),
+            None::<AccountOptions>,
         ))
         .deposit(NearToken::from_millinear(100))
         .transact()
@@ -618,11 +669,15 @@ async fn sim_claim() {
         .unwrap()
         .json::<AccountInfo>()
         .unwrap();
+    // the failed transfer is kept as pending instead of rolling the claim back
     assert_eq!(
-        user_info.unclaimed_amount.0, 100,
+        user_info.unclaimed_amount.0, 0,
         "user_info is {:?}",
         user_info
     );
+    assert_eq!(user_info.pending_amount.0, 100);
+    assert!(user_info.last_claim_error.is_some());
+    assert_eq!(user_info.in_flight_amount.0, 0);
 
     let res = user1
         .call(token.id(), "storage_deposit")
@@ -634,8 +689,9 @@ async fn sim_claim() {
     assert!(res.is_success(), "Res is {:?}", res);
 
     let res = user1
-        .call(session_vault.id(), "claim")
+        .call(session_vault.id(), "withdraw_pending")
         .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
         .max_gas()
         .transact()
         .await
@@ -659,6 +715,9 @@ async fn sim_claim() {
         .unwrap();
 
     assert_eq!(user_info.unclaimed_amount.0, 0);
+    assert_eq!(user_info.pending_amount.0, 0);
+    assert!(user_info.last_claim_error.is_none());
+    assert_eq!(user_info.in_flight_amount.0, 0);
 
     let user1_balance = token
         .view("ft_balance_of")
@@ -679,7 +738,14 @@ async fn sim_claim() {
 
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((user1.id(), U64(timestamp.max(1) - 1), U64(5), 2, U128(100)))
+        .args_json((
+            user1.id(),
+            U64(timestamp.max(1) - 1),
+            U64(5),
+            2,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -771,3 +837,2942 @@ async fn sim_claim() {
         .unwrap();
     assert_eq!(300, balance.0);
 }
+
+#[tokio::test]
+async fn sim_claim_policy() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(timestamp.max(1) - 1),
+            U64(1),
+            2,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(200),
+            Option::<String>::None,
+            user1.id(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // owner is not the beneficiary, so it can only claim when policy allows it
+    let res = owner
+        .call(session_vault.id(), "set_claim_policy")
+        .args_json(serde_json::json!({ "claim_policy": "SelfOnly" }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_ACCOUNT_NOT_EXIST"),
+        "got {:?}",
+        failure
+    );
+
+    let res = user1
+        .call(session_vault.id(), "set_claim_policy")
+        .args_json(serde_json::json!({ "claim_policy": "SelfOnly" }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(session_vault.id(), "claim")
+        .args_json((Some(user1.id()),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_CLAIM_NOT_ALLOWED"),
+        "got {:?}",
+        failure
+    );
+
+    let res = user1
+        .call(session_vault.id(), "set_claim_policy")
+        .args_json(serde_json::json!({ "claim_policy": { "Delegates": [owner.id()] } }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    wait_seconds(&root, 2).await;
+    let res = owner
+        .call(session_vault.id(), "claim")
+        .args_json((Some(user1.id()),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let balance = token
+        .view("ft_balance_of")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(200, balance.0);
+}
+
+#[tokio::test]
+async fn sim_distribute() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let keeper = root_account
+        .create_subaccount("keeper")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    let mut users = vec![];
+    for name in ["user1", "user2", "user3"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(10),
+                U64(10),
+                1,
+                U128(100),
+                None::<AccountOptions>,
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(100),
+                Option::<String>::None,
+                user.id(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+
+    let res = keeper
+        .call(session_vault.id(), "distribute")
+        .args_json((Option::<U64>::None, Some(2)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_NOT_ALLOWED"), "got {:?}", failure);
+
+    let res = owner
+        .call(session_vault.id(), "add_keeper")
+        .args_json((keeper.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // first page covers two accounts, the second one the last and wraps around
+    let res = keeper
+        .call(session_vault.id(), "distribute")
+        .args_json((Option::<U64>::None, Some(2)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 2);
+    let res = keeper
+        .call(session_vault.id(), "distribute")
+        .args_json((Option::<U64>::None, Some(2)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 0);
+
+    for user in &users {
+        let balance = token
+            .view("ft_balance_of")
+            .args_json((user.id(),))
+            .await
+            .unwrap()
+            .json::<U128>()
+            .unwrap();
+        assert_eq!(100, balance.0);
+    }
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert_eq!(contract_info.claimed_balance.0, 300);
+}
+
+#[tokio::test]
+async fn sim_account_transfer() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2", "user3"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        users.push(user);
+    }
+    let (user1, user2, user3) = (&users[0], &users[1], &users[2]);
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(session_vault.id(), "set_transfer_requires_approval")
+        .args_json((true,))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = user1
+        .call(session_vault.id(), "propose_account_transfer")
+        .args_json((Some(user2.id()),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = user3
+        .call(session_vault.id(), "accept_account_transfer")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_TRANSFER_NOT_PROPOSED"),
+        "got {:?}",
+        failure
+    );
+    let res = user2
+        .call(session_vault.id(), "accept_account_transfer")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_TRANSFER_NOT_APPROVED"),
+        "got {:?}",
+        failure
+    );
+
+    let res = owner
+        .call(session_vault.id(), "approve_account_transfer")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = user2
+        .call(session_vault.id(), "accept_account_transfer")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.deposited_amount.0, 100);
+    assert!(user_info.transfer_to.is_none());
+    assert!(session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .is_none());
+
+    // owner recovers a grant whose keys were lost
+    let res = owner
+        .call(session_vault.id(), "force_transfer_account")
+        .args_json((user2.id(), user3.id()))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user3.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.deposited_amount.0, 100);
+}
+
+#[tokio::test]
+async fn sim_calendar_schedule() {
+    let (root, owner, session_vault, _) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    // monthly sessions started 40 days ago, so exactly one is unlocked
+    const DAY: u64 = 86400;
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let start = timestamp - 40 * DAY;
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(start),
+            U64(1),
+            3,
+            U128(100),
+            Some(AccountOptions {
+                interval_unit: IntervalUnit::Month,
+                ..Default::default()
+            }),
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.interval_unit, IntervalUnit::Month);
+    assert_eq!(user_info.unclaimed_amount.0, 100);
+    // two calendar months are 59 to 62 days, three are 89 to 92 days
+    let next_unlock = user_info.next_unlock_timestamp.unwrap().0;
+    assert!((start + 59 * DAY..=start + 62 * DAY).contains(&next_unlock));
+    assert_eq!(next_unlock % DAY, start % DAY);
+    let end = user_info.end_timestamp.0;
+    assert!((start + 89 * DAY..=start + 92 * DAY).contains(&end));
+    assert_eq!(end % DAY, start % DAY);
+}
+
+#[tokio::test]
+async fn sim_remainder_total() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user2 = root_account
+        .create_subaccount("user2")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    for user in [&user1, &user2] {
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    // 100 over 7 sessions is 14 per session and a remainder of 2,
+    // 4 sessions of 1000 seconds are unlocked
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for (user, remainder_policy) in [
+        (&user1, RemainderPolicy::LastSession),
+        (&user2, RemainderPolicy::Spread),
+    ] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(timestamp - 4500),
+                U64(1000),
+                7,
+                None::<U128>,
+                Some(AccountOptions {
+                    total_amount: Some(U128(100)),
+                    remainder_policy,
+                    ..Default::default()
+                }),
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(98),
+                Option::<String>::None,
+                user.id(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        let receipt_failures = res.receipt_failures();
+        let first = format!("{:#?}", receipt_failures.first().unwrap());
+        assert!(
+            first.contains("ERR_INCORRECT_AMOUNT"),
+            "first is {:#?}",
+            first
+        );
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(100),
+                Option::<String>::None,
+                user.id(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.release_per_session.0, 14);
+    assert_eq!(user_info.total_amount.0, 100);
+    assert_eq!(user_info.deposited_amount.0, 100);
+    assert_eq!(user_info.unclaimed_amount.0, 56);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.remainder_policy, RemainderPolicy::Spread);
+    assert_eq!(user_info.unclaimed_amount.0, 57);
+
+    let res = user2
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.claimed_amount.0, 57);
+    assert_eq!(user_info.last_claim_session, 4);
+    assert_eq!(user_info.unclaimed_amount.0, 0);
+}
+
+#[tokio::test]
+async fn sim_milestone_account() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let attester = root_account
+        .create_subaccount("attester")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(session_vault.id(), "add_milestone_attester")
+        .args_json((attester.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    // no TGE or cliff for milestones
+    let res = owner
+        .call(session_vault.id(), "add_milestone_account")
+        .args_json((
+            user1.id(),
+            vec![MilestoneConfig {
+                description: "mainnet launch".to_string(),
+                amount: U128(60),
+                deadline: None,
+            }],
+            Some(AccountOptions {
+                tge_amount: U128(10),
+                ..Default::default()
+            }),
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_INVALID_MILESTONE_OPTIONS").await;
+    let res = owner
+        .call(session_vault.id(), "add_milestone_account")
+        .args_json((
+            user1.id(),
+            vec![
+                MilestoneConfig {
+                    description: "mainnet launch".to_string(),
+                    amount: U128(60),
+                    deadline: None,
+                },
+                MilestoneConfig {
+                    description: "integration shipped".to_string(),
+                    amount: U128(40),
+                    deadline: Some(U64(timestamp + 3)),
+                },
+            ],
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = user1
+        .call(session_vault.id(), "release_milestone")
+        .args_json((user1.id(), 0))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_NOT_ALLOWED"), "got {:?}", failure);
+
+    let res = attester
+        .call(session_vault.id(), "release_milestone")
+        .args_json((user1.id(), 0))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("EVENT_JSON:") && log.contains("milestone_released")));
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.session_num, 2);
+    assert_eq!(user_info.unclaimed_amount.0, 60);
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("EVENT_JSON:") && log.contains("milestone_claimed")));
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.claimed_amount.0, 60);
+    assert_eq!(user_info.last_claim_session, 1);
+    assert_eq!(user_info.milestones[0].status, MilestoneStatus::Claimed);
+
+    let res = owner
+        .call(session_vault.id(), "reclaim_milestone")
+        .args_json((user1.id(), 1))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_MILESTONE_NOT_EXPIRED"),
+        "got {:?}",
+        failure
+    );
+
+    // the second milestone missed its deadline, owner takes it back
+    wait_seconds(&root, 5).await;
+    let res = owner
+        .call(session_vault.id(), "reclaim_milestone")
+        .args_json((user1.id(), 1))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("EVENT_JSON:") && log.contains("milestone_reclaimed")));
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.deposited_amount.0, 60);
+    assert_eq!(user_info.total_amount.0, 60);
+    assert_eq!(user_info.milestones[1].status, MilestoneStatus::Reclaimed);
+}
+
+#[tokio::test]
+async fn sim_terminate_account() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user2 = root_account
+        .create_subaccount("user2")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    // 4 sessions of 1000 seconds, the first one is vested
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for user in [&user1, &user2] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(timestamp - 1500),
+                U64(1000),
+                4,
+                U128(25),
+                Some(AccountOptions {
+                    termination_policy: TerminationPolicy {
+                        good_leaver_acceleration: 1,
+                        bad_leaver_forfeits_unclaimed: true,
+                    },
+                    ..Default::default()
+                }),
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(100),
+                Option::<String>::None,
+                user.id(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    // good leaver keeps the vested session plus one accelerated
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user1.id(), TerminationKind::GoodLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res.logs().iter().any(|log| log.starts_with("EVENT_JSON:")
+        && log.contains("account_terminated")
+        && log.contains("\"kept_sessions\":2")));
+    assert_eq!(res.json::<U128>().unwrap().0, 50);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    let termination = user_info.termination.unwrap();
+    assert_eq!(termination.kind, TerminationKind::GoodLeaver);
+    assert_eq!(termination.kept_sessions, 2);
+    assert_eq!(user_info.deposited_amount.0, 50);
+    assert_eq!(user_info.unclaimed_amount.0, 50);
+
+    // bad leaver forfeits the vested but unclaimed session too
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user2.id(), TerminationKind::BadLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U128>().unwrap().0, 100);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.unclaimed_amount.0, 0);
+    assert_eq!(user_info.deposited_amount.0, 0);
+
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user2.id(), TerminationKind::GoodLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_ACCOUNT_TERMINATED"),
+        "got {:?}",
+        failure
+    );
+
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert_eq!(contract_info.total_balance.0, 50);
+    assert_eq!(&contract_info.treasury_id, owner.id());
+    assert_eq!(contract_info.treasury_pending.0, 0);
+}
+
+#[tokio::test]
+async fn sim_accelerate() {
+    let (root, owner, session_vault, _) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user2 = root_account
+        .create_subaccount("user2")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    // 5 sessions of 1000 seconds, the first one is unlocked
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for user in [&user1, &user2] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(timestamp - 1500),
+                U64(1000),
+                5,
+                U128(20),
+                None::<AccountOptions>,
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    let res = user1
+        .call(session_vault.id(), "accelerate")
+        .args_json((
+            Option::<Vec<AccountId>>::None,
+            5000,
+            Option::<U64>::None,
+            Option::<u32>::None,
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_NOT_ALLOWED"), "got {:?}", failure);
+
+    // half of the 4 remaining sessions of user1 unlock now
+    let res = owner
+        .call(session_vault.id(), "accelerate")
+        .args_json((
+            Some(vec![user1.id()]),
+            5000,
+            Option::<U64>::None,
+            Option::<u32>::None,
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("EVENT_JSON:") && log.contains("account_accelerated")));
+    assert_eq!(res.json::<u32>().unwrap(), 1);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.accelerated_sessions, 2);
+    assert_eq!(user_info.unclaimed_amount.0, 60);
+    assert_eq!(user_info.end_timestamp.0, timestamp - 1500 + 3000);
+
+    // then everything left for all accounts, a page of one at a time
+    for from_index in [0, 1] {
+        let res = owner
+            .call(session_vault.id(), "accelerate")
+            .args_json((
+                Option::<Vec<AccountId>>::None,
+                10000,
+                Some(U64(from_index)),
+                Some(1),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        assert_eq!(res.json::<u32>().unwrap(), 1);
+    }
+    for user in [&user1, &user2] {
+        let user_info = session_vault
+            .view("get_account")
+            .args_json((user.id(),))
+            .await
+            .unwrap()
+            .json::<AccountInfo>()
+            .unwrap();
+        assert_eq!(user_info.unclaimed_amount.0, 100);
+        assert!(user_info.next_unlock_timestamp.is_none());
+    }
+}
+
+#[tokio::test]
+async fn sim_claim_expiry() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user2 = root_account
+        .create_subaccount("user2")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user3 = root_account
+        .create_subaccount("user3")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let res = owner
+        .call(session_vault.id(), "set_claim_deadline")
+        .args_json((Some(U64(timestamp - 1)),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_INVALID_CLAIM_DEADLINE"),
+        "got {:?}",
+        failure
+    );
+    let res = owner
+        .call(session_vault.id(), "set_claim_deadline")
+        .args_json((Some(U64(timestamp + 1000)),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // user1 has its own deadline, user2 follows the vault
+    for (user, claim_deadline) in [(&user1, Some(U64(timestamp + 5))), (&user2, None)] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(timestamp - 10),
+                U64(1),
+                1,
+                U128(100),
+                Some(AccountOptions {
+                    claim_deadline,
+                    ..Default::default()
+                }),
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(100),
+                Option::<String>::None,
+                user.id(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.claim_deadline, Some(U64(timestamp + 1000)));
+    // user3 has a milestone grant with the deadline of user1
+    let res = owner
+        .call(session_vault.id(), "add_milestone_account")
+        .args_json((
+            user3.id(),
+            vec![
+                MilestoneConfig {
+                    description: "mainnet launch".to_string(),
+                    amount: U128(50),
+                    deadline: None,
+                },
+                MilestoneConfig {
+                    description: "integration shipped".to_string(),
+                    amount: U128(50),
+                    deadline: Some(U64(timestamp + 5)),
+                },
+            ],
+            Some(AccountOptions {
+                claim_deadline: Some(U64(timestamp + 5)),
+                ..Default::default()
+            }),
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user3.id(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(session_vault.id(), "sweep_expired_account")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_CLAIM_DEADLINE_NOT_PASSED"),
+        "got {:?}",
+        failure
+    );
+
+    wait_seconds(&root, 7).await;
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_CLAIM_EXPIRED"), "got {:?}", failure);
+
+    let res = owner
+        .call(session_vault.id(), "sweep_expired_account")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert!(user_info.expired);
+    assert_eq!(user_info.deposited_amount.0, 0);
+    assert_eq!(user_info.unclaimed_amount.0, 0);
+
+    // what was swept can't be taken back again
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user1.id(), TerminationKind::BadLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_EXPIRED").await;
+    let res = owner
+        .call(session_vault.id(), "sweep_expired_account")
+        .args_json((user3.id(),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "reclaim_milestone")
+        .args_json((user3.id(), 1))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_EXPIRED").await;
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert_eq!(contract_info.total_balance.0, 100);
+}
+
+#[tokio::test]
+async fn sim_close_vault() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2", "user3"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+    let (user1, user2, user3) = (&users[0], &users[1], &users[2]);
+
+    // user2 is never funded, his sessions are still to unlock
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for (user, start) in [(user1, timestamp - 10), (user2, timestamp + 1000)] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(start),
+                U64(1),
+                1,
+                U128(100),
+                None::<AccountOptions>,
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+    // funds the account and leaves some dust in the burn pool
+    for (amount, msg) in [(100, user1.id().to_string()), (7, "BURN_POOL".to_string())] {
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(amount),
+                Option::<String>::None,
+                msg,
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    let res = owner
+        .call(session_vault.id(), "check_settled")
+        .args_json((Option::<u32>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_NOT_SETTLED: user1").await;
+    let res = owner
+        .call(session_vault.id(), "close_vault")
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_SETTLEMENT_NOT_CHECKED").await;
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "check_settled")
+        .args_json((Some(1),))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 1);
+    let res = owner
+        .call(session_vault.id(), "check_settled")
+        .args_json((Option::<u32>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_NOT_SETTLED: user2").await;
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user2.id(), TerminationKind::BadLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    // nor can it be funded anymore
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user2.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_TERMINATED").await;
+    let res = owner
+        .call(session_vault.id(), "check_settled")
+        .args_json((Option::<u32>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 2);
+
+    // a grant added after the check has to be checked too
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user3.id(),
+            U64(timestamp + 1000),
+            U64(1),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "close_vault")
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_SETTLEMENT_NOT_CHECKED").await;
+    let res = owner
+        .call(session_vault.id(), "terminate_account")
+        .args_json((user3.id(), TerminationKind::BadLeaver))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "check_settled")
+        .args_json((Option::<u32>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 3);
+
+    let res = owner
+        .call(session_vault.id(), "close_vault")
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res.logs().iter().any(|log| log.starts_with("EVENT_JSON:")
+        && log.contains("vault_closed")
+        && log.contains("\"treasury_pending\"")));
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert!(contract_info.closed);
+    assert_eq!(contract_info.burn_pool.0, 0);
+    let vault_balance = token
+        .view("ft_balance_of")
+        .args_json((session_vault.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(vault_balance.0, 0);
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(timestamp + 10),
+            U64(1),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_VAULT_CLOSED"), "got {:?}", failure);
+}
+
+#[tokio::test]
+async fn sim_funders() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "funder1"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+    let (user1, funder1) = (&users[0], &users[1]);
+    let res = owner
+        .call(token.id(), "ft_transfer")
+        .args_json((funder1.id(), U128(100), Option::<String>::None))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // not a funder yet, refunded
+    let res = funder1
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let balance = token
+        .view("ft_balance_of")
+        .args_json((funder1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(balance.0, 100);
+    let account_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(account_info.deposited_amount.0, 0);
+
+    let res = owner
+        .call(session_vault.id(), "add_funder")
+        .args_json((funder1.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "set_record_funders")
+        .args_json((true,))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let funders = session_vault
+        .view("get_funders")
+        .await
+        .unwrap()
+        .json::<Vec<AccountId>>()
+        .unwrap();
+    assert_eq!(funders, vec![funder1.id().clone()]);
+
+    let res = funder1
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let account_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(account_info.deposited_amount.0, 100);
+    assert_eq!(account_info.funder_id, Some(funder1.id().clone()));
+}
+
+#[tokio::test]
+async fn sim_multi_token() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let other_token = test_token(
+        &root,
+        &root_account,
+        AccountId::from_str("other_token").unwrap(),
+        vec![session_vault.id().clone(), owner.id().clone()],
+    )
+    .await
+    .unwrap();
+    let res = owner
+        .call(other_token.id(), "mint")
+        .args_json((U128(10000),))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(other_token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let options = AccountOptions {
+        token_id: Some(other_token.id().clone()),
+        ..Default::default()
+    };
+    let add_account = || {
+        owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user1.id(),
+                U64(timestamp - 10),
+                U64(1),
+                1,
+                U128(100),
+                Some(options.clone()),
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+    };
+    let res = add_account().await.unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(failure.contains("ERR_ILLEGAL_TOKEN"), "got {:?}", failure);
+
+    let res = owner
+        .call(session_vault.id(), "add_token")
+        .args_json((other_token.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = add_account().await.unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // the grant only takes its own token
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    let failure = format!("{:?}", res.receipt_failures().first());
+    assert!(failure.contains("ERR_ILLEGAL_TOKEN"), "got {:?}", failure);
+    let res = owner
+        .call(other_token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let balance = other_token
+        .view("ft_balance_of")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(balance.0, 100);
+
+    let account_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(account_info.token_id, Some(other_token.id().clone()));
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert_eq!(contract_info.total_balance.0, 0);
+    assert_eq!(contract_info.claimed_balance.0, 0);
+    let other = contract_info
+        .tokens
+        .iter()
+        .find(|info| &info.token_id == other_token.id())
+        .unwrap();
+    assert!(other.whitelisted);
+    assert_eq!(other.total_balance.0, 100);
+    assert_eq!(other.claimed_balance.0, 100);
+}
+
+#[tokio::test]
+async fn sim_native_grant() {
+    let (root, owner, session_vault, _token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(timestamp - 10),
+            U64(1),
+            1,
+            U128(NearToken::from_near(2).as_yoctonear()),
+            Some(AccountOptions {
+                native: true,
+                ..Default::default()
+            }),
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // not a funder, refunded
+    let res = user1
+        .call(session_vault.id(), "fund_account_native")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_near(2))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(!res.json::<bool>().unwrap());
+    let res = owner
+        .call(session_vault.id(), "fund_account_native")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_near(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_INCORRECT_AMOUNT"),
+        "got {:?}",
+        failure
+    );
+    let res = owner
+        .call(session_vault.id(), "fund_account_native")
+        .args_json((user1.id(),))
+        .deposit(NearToken::from_near(2))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res.json::<bool>().unwrap());
+
+    let balance_before = user1.view_account().await.unwrap().balance;
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let balance_after = user1.view_account().await.unwrap().balance;
+    assert!(balance_after > balance_before.saturating_add(NearToken::from_millinear(1900)));
+
+    let account_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert!(account_info.native);
+    assert_eq!(account_info.token_id, None);
+    let contract_info = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json::<ContractInfo>()
+        .unwrap();
+    assert_eq!(contract_info.total_balance.0, 0);
+    assert_eq!(
+        contract_info.native_balance.total_balance.0,
+        NearToken::from_near(2).as_yoctonear()
+    );
+    assert_eq!(
+        contract_info.native_balance.claimed_balance.0,
+        NearToken::from_near(2).as_yoctonear()
+    );
+}
+
+#[tokio::test]
+async fn sim_vesting_template() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        users.push(user);
+    }
+    let (user1, user2) = (&users[0], &users[1]);
+
+    // 10% at TGE, then 4 sessions of 1000 seconds unlocking from 2000 seconds after start
+    let template = VestingTemplate {
+        cliff: U64(2000),
+        session_interval: U64(1000),
+        session_num: 4,
+        tge_bps: 1000,
+        interval_unit: IntervalUnit::Second,
+        category: None,
+    };
+    let res = owner
+        .call(session_vault.id(), "set_template")
+        .args_json(("strategic", template.clone()))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let stored = session_vault
+        .view("get_template")
+        .args_json(("strategic",))
+        .await
+        .unwrap()
+        .json::<Option<VestingTemplate>>()
+        .unwrap();
+    assert_eq!(stored, Some(template));
+
+    let res = owner
+        .call(session_vault.id(), "add_account_from_template")
+        .args_json((user1.id(), "private", U128(1000), U64(10)))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_TEMPLATE_NOT_EXIST").await;
+
+    // user1 is past the cliff with 2 sessions vested, user2 only has the TGE share
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for (user, start) in [(user1, timestamp - 2500), (user2, timestamp - 1500)] {
+        let res = owner
+            .call(session_vault.id(), "add_account_from_template")
+            .args_json((user.id(), "strategic", U128(1000), U64(start)))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(1000),
+                Option::<String>::None,
+                user.id().to_string(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.session_num, 5);
+    assert_eq!(user_info.tge_amount.0, 100);
+    assert_eq!(user_info.cliff.0, 2000);
+    assert_eq!(user_info.release_per_session.0, 225);
+    assert_eq!(user_info.total_amount.0, 1000);
+    assert_eq!(user_info.unclaimed_amount.0, 550);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.unclaimed_amount.0, 100);
+    assert_eq!(
+        user_info.next_unlock_timestamp,
+        Some(U64(timestamp - 1500 + 2000))
+    );
+}
+
+#[tokio::test]
+async fn sim_categories() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+    let (user1, user2) = (&users[0], &users[1]);
+
+    let res = owner
+        .call(session_vault.id(), "set_category")
+        .args_json(("team", U128(1000)))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let team = || AccountOptions {
+        category: Some("team".to_string()),
+        ..Default::default()
+    };
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((user1.id(), U64(10), U64(10), 1, U128(600), Some(team())))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((user2.id(), U64(10), U64(10), 1, U128(500), Some(team())))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_CATEGORY_CAP_EXCEEDED").await;
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user2.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(500),
+            Some(AccountOptions {
+                category: Some("advisors".to_string()),
+                ..Default::default()
+            }),
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_CATEGORY_NOT_EXIST").await;
+
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(600),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "set_category")
+        .args_json(("team", U128(500)))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_CAP_BELOW_ALLOCATED").await;
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let categories = session_vault
+        .view("get_categories")
+        .await
+        .unwrap()
+        .json::<Vec<CategoryInfo>>()
+        .unwrap();
+    assert_eq!(
+        categories,
+        vec![CategoryInfo {
+            category: "team".to_string(),
+            cap: U128(1000),
+            allocated: U128(600),
+            deposited: U128(600),
+            claimed: U128(600),
+            nft_transfer_disabled: false,
+            locked: U128(0),
+        }]
+    );
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.category, Some("team".to_string()));
+}
+
+#[tokio::test]
+async fn sim_grant_nft() {
+    let (root, owner, session_vault, _token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        users.push(user);
+    }
+    let (user1, user2) = (&users[0], &users[1]);
+
+    let res = owner
+        .call(session_vault.id(), "set_nft_enabled")
+        .args_json((true,))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let nft = session_vault
+        .view("nft_token")
+        .args_json(("0",))
+        .await
+        .unwrap()
+        .json::<Option<Token>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(&nft.owner_id, user1.id());
+
+    let res = user2
+        .call(session_vault.id(), "nft_transfer")
+        .args_json((user2.id(), "0", Option::<u64>::None, Option::<String>::None))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_NOT_TOKEN_OWNER").await;
+    let res = user1
+        .call(session_vault.id(), "nft_transfer")
+        .args_json((user2.id(), "0", Option::<u64>::None, Option::<String>::None))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.nft_token_id, Some("0".to_string()));
+    assert_eq!(user_info.total_amount.0, 100);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap();
+    assert!(user_info.is_none());
+
+    let res = owner
+        .call(session_vault.id(), "set_account_nft_transferable")
+        .args_json((user2.id(), false))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = user2
+        .call(session_vault.id(), "nft_transfer")
+        .args_json((user1.id(), "0", Option::<u64>::None, Option::<String>::None))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_NFT_TRANSFER_DISABLED").await;
+}
+
+#[tokio::test]
+async fn sim_voting_power() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        users.push(user);
+    }
+    let (user1, user2) = (&users[0], &users[1]);
+
+    let res = owner
+        .call(session_vault.id(), "set_voting_checkpoints")
+        .args_json((true,))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    for user in [user1, user2] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(10),
+                U64(10),
+                1,
+                U128(100),
+                None::<AccountOptions>,
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let power = session_vault
+        .view("get_voting_power")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<VotingPower>()
+        .unwrap();
+    assert_eq!(power.locked.0 + power.unclaimed.0, 100);
+    // user2 is past his start, unfunded
+    let power = session_vault
+        .view("get_voting_power")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<VotingPower>()
+        .unwrap();
+    assert_eq!(power.locked.0, 0);
+    assert_eq!(power.unclaimed.0, 0);
+    let snapshot = session_vault
+        .view("get_voting_snapshot")
+        .args_json((Option::<U64>::None, Option::<U64>::None))
+        .await
+        .unwrap()
+        .json::<Vec<(AccountId, U128)>>()
+        .unwrap();
+    assert_eq!(snapshot, vec![(user1.id().clone(), U128(100))]);
+
+    let checkpoints = session_vault
+        .view("get_checkpoints")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Vec<Checkpoint>>()
+        .unwrap();
+    assert_eq!(checkpoints.len(), 1);
+    assert_eq!(checkpoints[0].amount.0, 100);
+    let locked = session_vault
+        .view("get_locked_at")
+        .args_json((user1.id(), checkpoints[0].block_height))
+        .await
+        .unwrap()
+        .json::<Option<U128>>()
+        .unwrap();
+    assert_eq!(locked, Some(U128(100)));
+    let locked = session_vault
+        .view("get_locked_at")
+        .args_json((user1.id(), U64(checkpoints[0].block_height.0 - 1)))
+        .await
+        .unwrap()
+        .json::<Option<U128>>()
+        .unwrap();
+    assert!(locked.is_none());
+    let locked = session_vault
+        .view("get_locked_at")
+        .args_json((user2.id(), checkpoints[0].block_height))
+        .await
+        .unwrap()
+        .json::<Option<U128>>()
+        .unwrap();
+    assert!(locked.is_none());
+}
+
+#[tokio::test]
+async fn sim_staking() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "staking"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        users.push(user);
+    }
+    // the staking contract is not registered with the token, so stakes are refunded
+    let (user1, staking) = (&users[0], &users[1]);
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(4_000_000_000),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = user1
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), staking.id(), U128(60)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_NOT_ALLOWED").await;
+    let res = owner
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), staking.id(), U128(60)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_STAKING_CONTRACT_NOT_WHITELISTED").await;
+
+    let res = owner
+        .call(session_vault.id(), "add_staking_contract")
+        .args_json((staking.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), staking.id(), U128(200)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_NOT_ENOUGH_LOCKED").await;
+    let res = owner
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), staking.id(), U128(60)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U128>().unwrap().0, 0);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.staked_amount.0, 0);
+    assert!(user_info.staking_id.is_none());
+
+    let res = user1
+        .call(session_vault.id(), "unstake")
+        .args_json((user1.id(), U128(60)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_INVALID_AMOUNT").await;
+
+    // only the staking contract of the account can send it back
+    let owner_balance = balance_of(&root, &token, owner.id()).await.unwrap();
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(60),
+            Option::<String>::None,
+            format!("UNSTAKE:{}", user1.id()),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U128>().unwrap().0, 0);
+    assert_eq!(
+        balance_of(&root, &token, owner.id()).await.unwrap(),
+        owner_balance
+    );
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.staking_rewards.0, 0);
+}
+
+#[tokio::test]
+async fn sim_distribute_staked() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "user2", "keeper"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+    let (user1, user2, keeper) = (&users[0], &users[1], &users[2]);
+    // another vault takes the stake, funding a grant of user1 there
+    let farm = deploy_session_vault(&root_account, "farm", owner.id(), token.id()).await;
+    let res = owner
+        .call(farm.id(), "add_funder")
+        .args_json((session_vault.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(farm.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    for (user, start, amount) in [(user1, timestamp + 20, 100), (user2, 10, 50)] {
+        let res = owner
+            .call(session_vault.id(), "add_account")
+            .args_json((
+                user.id(),
+                U64(start),
+                U64(10),
+                1,
+                U128(amount),
+                None::<AccountOptions>,
+            ))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        let res = owner
+            .call(token.id(), "ft_transfer_call")
+            .args_json((
+                session_vault.id(),
+                U128(amount),
+                Option::<String>::None,
+                user.id().to_string(),
+            ))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+    }
+
+    let res = owner
+        .call(session_vault.id(), "add_staking_contract")
+        .args_json((farm.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), farm.id(), U128(100)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U128>().unwrap().0, 100);
+    let res = owner
+        .call(session_vault.id(), "add_keeper")
+        .args_json((keeper.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // user1 unlocked all of it while it is staked
+    wait_seconds(&root, 35).await;
+    let res = keeper
+        .call(session_vault.id(), "distribute")
+        .args_json((Some(U64(0)), Option::<u32>::None))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U64>().unwrap().0, 0);
+    assert_eq!(balance_of(&root, &token, user2.id()).await.unwrap(), 50);
+    assert_eq!(balance_of(&root, &token, user1.id()).await.unwrap(), 0);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.staked_amount.0, 100);
+    assert_eq!(user_info.unclaimed_amount.0, 100);
+}
+
+#[tokio::test]
+async fn sim_staking_rewards() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    // pays 10% rewards out of what owner sends it
+    let staking = deploy_mock_staking(&root_account, "staking", token.id(), 10).await;
+    let res = owner
+        .call(token.id(), "ft_transfer")
+        .args_json((staking.id(), U128(100), Option::<String>::None))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(timestamp + 10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "add_staking_contract")
+        .args_json((staking.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), staking.id(), U128(100)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<U128>().unwrap().0, 100);
+    let staked = staking
+        .view("get_staked")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(staked.0, 100);
+
+    // all of it unlocks while it is staked
+    wait_seconds(&root, 25).await;
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_STAKED_NOT_WITHDRAWN").await;
+
+    let res = user1
+        .call(session_vault.id(), "unstake")
+        .args_json((user1.id(), U128(100)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.staked_amount.0, 0);
+    assert!(user_info.staking_id.is_none());
+    assert_eq!(user_info.staking_rewards.0, 10);
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(balance_of(&root, &token, user1.id()).await.unwrap(), 100);
+    let res = user1
+        .call(session_vault.id(), "withdraw_staking_rewards")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(balance_of(&root, &token, user1.id()).await.unwrap(), 110);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.staking_rewards.0, 0);
+    assert_eq!(user_info.unclaimed_amount.0, 0);
+}
+
+#[tokio::test]
+async fn sim_freeze_account() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let mut users = vec![];
+    for name in ["user1", "officer"] {
+        let user = root_account
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await
+            .unwrap()
+            .result;
+        let res = user
+            .call(token.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))
+            .deposit(NearToken::from_near(1))
+            .transact()
+            .await
+            .unwrap();
+        assert!(res.is_success(), "Res is {:?}", res);
+        users.push(user);
+    }
+    let (user1, officer) = (&users[0], &users[1]);
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id().to_string(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = officer
+        .call(session_vault.id(), "freeze_account")
+        .args_json((user1.id(), "SANCTIONS_REVIEW"))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_NOT_ALLOWED").await;
+    let res = owner
+        .call(session_vault.id(), "add_compliance_officer")
+        .args_json((officer.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = officer
+        .call(session_vault.id(), "freeze_account")
+        .args_json((user1.id(), "SANCTIONS_REVIEW"))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert!(res
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("EVENT_JSON:") && log.contains("account_frozen")));
+
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_FROZEN").await;
+    let res = user1
+        .call(session_vault.id(), "set_claim_policy")
+        .args_json(serde_json::json!({ "claim_policy": "SelfOnly" }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_FROZEN").await;
+    let res = user1
+        .call(session_vault.id(), "propose_account_transfer")
+        .args_json((Some(officer.id()),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_FROZEN").await;
+    // nor stake or unstake his grant himself
+    let res = owner
+        .call(session_vault.id(), "add_staking_contract")
+        .args_json((officer.id(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(session_vault.id(), "set_beneficiary_staking")
+        .args_json((true,))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = user1
+        .call(session_vault.id(), "stake")
+        .args_json((user1.id(), officer.id(), U128(50)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_FROZEN").await;
+    let res = user1
+        .call(session_vault.id(), "unstake")
+        .args_json((user1.id(), U128(50)))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    error_contains(&res, "ERR_ACCOUNT_FROZEN").await;
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    let freeze = user_info.freeze.unwrap();
+    assert_eq!(freeze.reason_code, "SANCTIONS_REVIEW");
+    assert_eq!(&freeze.frozen_by, officer.id());
+    assert_eq!(user_info.unclaimed_amount.0, 100);
+
+    let res = officer
+        .call(session_vault.id(), "unfreeze_account")
+        .args_json((user1.id(), "REVIEW_CLEARED"))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = user1
+        .call(session_vault.id(), "claim")
+        .args_json((Option::<AccountId>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert!(user_info.freeze.is_none());
+    assert_eq!(user_info.claimed_amount.0, 100);
+}
@@ -0,0 +1,162 @@
+use crate::common::init::*;
+use crate::common::types::*;
+use near_sdk::{
+    borsh,
+    json_types::{U128, U64},
+    AccountId, NearToken,
+};
+use session_vault::MerkleAllocation;
+use sha2::{Digest, Sha256};
+
+pub mod common;
+
+fn leaf(account_id: &AccountId, allocation: &MerkleAllocation) -> [u8; 32] {
+    Sha256::digest(borsh::to_vec(&(account_id, allocation)).unwrap()).into()
+}
+
+fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    Sha256::digest([a, b].concat()).into()
+}
+
+#[tokio::test]
+async fn sim_merkle_allocation() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user2 = root_account
+        .create_subaccount("user2")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let allocation1 = MerkleAllocation {
+        start_timestamp: U64(10),
+        session_interval: U64(10),
+        session_num: 2,
+        amount: U128(200),
+    };
+    let allocation2 = MerkleAllocation {
+        start_timestamp: U64(10),
+        session_interval: U64(10),
+        session_num: 4,
+        amount: U128(400),
+    };
+    let leaf1 = leaf(user1.id(), &allocation1);
+    let leaf2 = leaf(user2.id(), &allocation2);
+    let merkle_root = parent(leaf1, leaf2);
+
+    let res = owner
+        .call(session_vault.id(), "set_merkle_root")
+        .args_json((Some(hex::encode(merkle_root)),))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(600),
+            Option::<String>::None,
+            "MERKLE_POOL",
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // a proof doesn't match another allocation
+    let res = user2
+        .call(session_vault.id(), "claim_with_proof")
+        .args_json((&allocation1, vec![hex::encode(leaf1)]))
+        .deposit(NearToken::from_millinear(100))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_INVALID_MERKLE_PROOF"),
+        "got {:?}",
+        failure
+    );
+
+    let res = user1
+        .call(session_vault.id(), "claim_with_proof")
+        .args_json((&allocation1, vec![hex::encode(leaf2)]))
+        .deposit(NearToken::from_millinear(100))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let balance = token
+        .view("ft_balance_of")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(200, balance.0);
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<AccountInfo>()
+        .unwrap();
+    assert_eq!(user_info.claimed_amount.0, 200);
+    assert!(session_vault
+        .view("get_account")
+        .args_json((user2.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .is_none());
+    let contract_info: ContractInfo = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(contract_info.merkle_pool.0, 400);
+
+    let res = user1
+        .call(session_vault.id(), "claim_with_proof")
+        .args_json((&allocation1, vec![hex::encode(leaf2)]))
+        .deposit(NearToken::from_millinear(100))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_ACCOUNT_ALREADY_EXIST"),
+        "got {:?}",
+        failure
+    );
+}
@@ -76,7 +76,14 @@ async fn sim_one_round_scenario_1() {
     println!("timestamp is {timestamp}");
     let res: ExecutionFinalResult = owner
         .call(session_vault.id(), "add_account")
-        .args_json((alice.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            alice.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -86,7 +93,14 @@ async fn sim_one_round_scenario_1() {
     // start from 100 sec, and release 100 token per 100 sec for 4 times, so the end is 500 sec.
     let res: ExecutionFinalResult = owner
         .call(session_vault.id(), "add_account")
-        .args_json((bob.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            bob.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -95,7 +109,14 @@ async fn sim_one_round_scenario_1() {
 
     let res: ExecutionFinalResult = owner
         .call(session_vault.id(), "add_account")
-        .args_json((charlie.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            charlie.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -938,7 +959,14 @@ async fn sim_one_round_scenario_2() {
     println!("timestamp is {timestamp}");
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((alice.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            alice.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -947,7 +975,14 @@ async fn sim_one_round_scenario_2() {
 
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((bob.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            bob.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
@@ -956,7 +991,14 @@ async fn sim_one_round_scenario_2() {
 
     let res = owner
         .call(session_vault.id(), "add_account")
-        .args_json((charlie.id(), U64(timestamp + 10), U64(10), 4, U128(100)))
+        .args_json((
+            charlie.id(),
+            U64(timestamp + 10),
+            U64(10),
+            4,
+            U128(100),
+            None::<AccountOptions>,
+        ))
         .deposit(NearToken::from_millinear(100))
         .transact()
         .await
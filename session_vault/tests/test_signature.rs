@@ -0,0 +1,390 @@
+use crate::common::init::*;
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_sdk::{
+    borsh,
+    json_types::{Base64VecU8, U128, U64},
+    AccountId, NearToken,
+};
+use session_vault::{AttestorSignature, BurnAttestation, BurnConfig, ClaimPayload};
+use sha3::{Digest, Keccak256};
+
+use crate::common::types::{AccountInfo, AccountOptions, ContractInfo};
+
+pub mod common;
+
+#[tokio::test]
+async fn sim_claim_with_signature() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let relayer = root_account
+        .create_subaccount("relayer")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let res = user1
+        .call(token.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let timestamp = root.view_block().await.unwrap().timestamp() / 10u64.pow(9);
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            user1.id(),
+            U64(timestamp.max(10) - 10),
+            U64(1),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            user1.id(),
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let claim_key = SecretKey::from_random(KeyType::ED25519);
+    let res = user1
+        .call(session_vault.id(), "set_claim_public_key")
+        .args_json((claim_key.public_key().to_string(),))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let payload = ClaimPayload {
+        account_id: user1.id().clone(),
+        receiver_id: user1.id().clone(),
+        nonce: U64(1),
+        expires_at: U64(timestamp + 3600),
+    };
+    let sign = |key: &SecretKey, payload: &ClaimPayload| -> Base64VecU8 {
+        let message = borsh::to_vec(&(session_vault.id().clone(), payload)).unwrap();
+        match key.sign(&message) {
+            Signature::ED25519(signature) => signature.to_bytes().to_vec().into(),
+            _ => unreachable!(),
+        }
+    };
+
+    let other_key = SecretKey::from_random(KeyType::ED25519);
+    let res = relayer
+        .call(session_vault.id(), "claim_with_signature")
+        .args_json((payload.clone(), sign(&other_key, &payload)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_INVALID_SIGNATURE"),
+        "got {:?}",
+        failure
+    );
+
+    let res = relayer
+        .call(session_vault.id(), "claim_with_signature")
+        .args_json((payload.clone(), sign(&claim_key, &payload)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let balance = token
+        .view("ft_balance_of")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<U128>()
+        .unwrap();
+    assert_eq!(100, balance.0);
+
+    let res = relayer
+        .call(session_vault.id(), "claim_with_signature")
+        .args_json((payload.clone(), sign(&claim_key, &payload)))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_NONCE_ALREADY_USED"),
+        "got {:?}",
+        failure
+    );
+}
+
+#[tokio::test]
+async fn sim_link_evm_account() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let user1 = root_account
+        .create_subaccount("user1")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+
+    let evm_key = SecretKey::from_random(KeyType::SECP256K1);
+    let evm_address = format!(
+        "0x{}",
+        hex::encode(&Keccak256::digest(evm_key.public_key().key_data())[12..])
+    );
+
+    let res = owner
+        .call(session_vault.id(), "add_account")
+        .args_json((
+            &evm_address,
+            U64(10),
+            U64(10),
+            1,
+            U128(100),
+            None::<AccountOptions>,
+        ))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(100),
+            Option::<String>::None,
+            &evm_address,
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    // signature for another NEAR account can't be used to link user1
+    let message: String = session_vault
+        .view("get_evm_link_message")
+        .args_json((&evm_address, owner.id()))
+        .await
+        .unwrap()
+        .json()
+        .unwrap();
+    let signature = personal_sign(&evm_key, &message);
+    let res = user1
+        .call(session_vault.id(), "link_evm_account")
+        .args_json((&evm_address, user1.id(), &signature))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_INVALID_SIGNATURE"),
+        "got {:?}",
+        failure
+    );
+
+    let message: String = session_vault
+        .view("get_evm_link_message")
+        .args_json((&evm_address, user1.id()))
+        .await
+        .unwrap()
+        .json()
+        .unwrap();
+    let signature = personal_sign(&evm_key, &message);
+    let res = user1
+        .call(session_vault.id(), "link_evm_account")
+        .args_json((&evm_address, user1.id(), &signature))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((user1.id(),))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.deposited_amount.0, 100);
+    let evm_info = session_vault
+        .view("get_account")
+        .args_json((&evm_address,))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap();
+    assert!(evm_info.is_none());
+}
+
+fn personal_sign(key: &SecretKey, message: &str) -> String {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message.as_bytes());
+    match key.sign(&Keccak256::digest(&prefixed)) {
+        Signature::SECP256K1(signature) => hex::encode(<[u8; 65]>::from(signature)),
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn sim_burn_attestation() {
+    let (root, owner, session_vault, token) = setup_vault().await;
+    let root_account = root.root_account().unwrap();
+    let relayer = root_account
+        .create_subaccount("relayer")
+        .initial_balance(NearToken::from_near(10))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let user1: AccountId = "user1.test.near".parse().unwrap();
+
+    let attestors: Vec<SecretKey> = (0..3)
+        .map(|_| SecretKey::from_random(KeyType::ED25519))
+        .collect();
+    let burn_config = BurnConfig {
+        attestors: attestors
+            .iter()
+            .map(|key| key.public_key().to_string().parse().unwrap())
+            .collect(),
+        threshold: 2,
+        start_timestamp: U64(10),
+        session_interval: U64(10),
+        session_num: 3,
+    };
+    let res = owner
+        .call(session_vault.id(), "set_burn_config")
+        .args_json((Some(burn_config),))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(token.id(), "ft_transfer_call")
+        .args_json((
+            session_vault.id(),
+            U128(1000),
+            Option::<String>::None,
+            "BURN_POOL",
+        ))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let attestation = BurnAttestation {
+        tx_hash: "0x9f0c1e3a0c4b5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6".to_string(),
+        burner: "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string(),
+        amount: U128(100),
+        account_id: user1.clone(),
+    };
+    let message = borsh::to_vec(&(session_vault.id().clone(), &attestation)).unwrap();
+    let signatures: Vec<AttestorSignature> = attestors
+        .iter()
+        .map(|key| AttestorSignature {
+            public_key: key.public_key().to_string().parse().unwrap(),
+            signature: match key.sign(&message) {
+                Signature::ED25519(signature) => signature.to_bytes().to_vec().into(),
+                _ => unreachable!(),
+            },
+        })
+        .collect();
+
+    let res = relayer
+        .call(session_vault.id(), "submit_burn_attestation")
+        .args_json((&attestation, &signatures[..1]))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_NOT_ENOUGH_ATTESTATIONS"),
+        "got {:?}",
+        failure
+    );
+
+    let res = relayer
+        .call(session_vault.id(), "submit_burn_attestation")
+        .args_json((&attestation, &signatures[1..]))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    // 100 over 3 sessions, the remaining 1 stays in the pool
+    assert_eq!(res.json::<U128>().unwrap().0, 99);
+
+    let user_info = session_vault
+        .view("get_account")
+        .args_json((&user1,))
+        .await
+        .unwrap()
+        .json::<Option<AccountInfo>>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(user_info.deposited_amount.0, 99);
+    assert_eq!(user_info.release_per_session.0, 33);
+    let contract_info: ContractInfo = session_vault
+        .view("contract_metadata")
+        .await
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(contract_info.burn_pool.0, 901);
+    assert_eq!(contract_info.total_balance.0, 99);
+
+    let res = relayer
+        .call(session_vault.id(), "submit_burn_attestation")
+        .args_json((&attestation, &signatures))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_BURN_ALREADY_PROCESSED"),
+        "got {:?}",
+        failure
+    );
+}
@@ -0,0 +1,165 @@
+/*!
+* session_vault factory contract
+*
+* Keeps the session_vault code, deploys a vault per round as its sub-account,
+* and upgrades the vaults it deployed.
+*/
+
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::json_types::{Base58CryptoHash, U128, U64};
+use near_sdk::serde_json::json;
+use near_sdk::store::IterableMap;
+use near_sdk::{
+    env, is_promise_success, log, near, AccountId, BorshStorageKey, NearToken, PanicOnDefault,
+    Promise,
+};
+
+use crate::utils::*;
+mod owner;
+mod utils;
+mod views;
+
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum StorageKeys {
+    Vaults,
+}
+
+#[derive(Clone)]
+#[near(serializers = [borsh, json])]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct VaultInfo {
+    pub vault_id: AccountId,
+    // round of the vault, eg: strategic, private or team
+    pub label: String,
+    pub token_id: AccountId,
+    // hash of the code the vault runs
+    pub code_hash: Base58CryptoHash,
+    pub created_at: TimestampSec,
+}
+
+#[derive(PanicOnDefault)]
+#[near(serializers=[borsh], contract_state)]
+pub struct Contract {
+    // owner of this contract
+    owner_id: AccountId,
+
+    // hash of the stored session_vault code, the code itself is under VAULT_CODE_KEY
+    code_hash: Option<Base58CryptoHash>,
+
+    // vaults deployed by this factory
+    vaults: IterableMap<AccountId, VaultInfo>,
+}
+
+#[near]
+impl Contract {
+    #[private]
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        Self {
+            owner_id,
+            code_hash: None,
+            vaults: IterableMap::new(StorageKeys::Vaults),
+        }
+    }
+
+    /// Deploy the stored code to `name`.<factory> and init it with `owner_id` and `token_id`,
+    /// in one batch. Attached deposit is the balance of the new vault, which pays its storage.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn create_vault(
+        &mut self,
+        name: String,
+        label: String,
+        owner_id: AccountId,
+        token_id: AccountId,
+    ) -> Promise {
+        self.assert_owner();
+        assert!(!name.contains('.'), "ERR_INVALID_VAULT_NAME");
+        let vault_id: AccountId = format!("{}.{}", name, env::current_account_id())
+            .parse()
+            .expect("ERR_INVALID_VAULT_NAME");
+        assert!(!label.is_empty(), "ERR_INVALID_LABEL");
+        assert!(
+            !self.vaults.contains_key(&vault_id),
+            "ERR_VAULT_ALREADY_EXIST"
+        );
+        let code = env::storage_read(VAULT_CODE_KEY).expect("ERR_NO_VAULT_CODE");
+        let amount = env::attached_deposit();
+        let min_amount = env::storage_byte_cost()
+            .checked_mul(code.len() as u128 + VAULT_INIT_STORAGE)
+            .unwrap();
+        assert!(amount >= min_amount, "ERR_NOT_ENOUGH_DEPOSIT");
+
+        self.vaults.insert(
+            vault_id.clone(),
+            VaultInfo {
+                vault_id: vault_id.clone(),
+                label,
+                token_id: token_id.clone(),
+                code_hash: self.code_hash.unwrap(),
+                created_at: U64(env::block_timestamp() / 10u64.pow(9)),
+            },
+        );
+        Promise::new(vault_id.clone())
+            .create_account()
+            .transfer(amount)
+            .deploy_contract(code)
+            .function_call(
+                "new".to_string(),
+                json!({ "owner_id": owner_id, "token_id": token_id })
+                    .to_string()
+                    .into_bytes(),
+                NO_DEPOSIT,
+                GAS_FOR_VAULT_INIT,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(NO_DEPOSIT)
+                    .with_static_gas(GAS_FOR_AFTER_CREATE_VAULT)
+                    .after_create_vault(
+                        vault_id,
+                        env::predecessor_account_id(),
+                        amount.as_yoctonear().into(),
+                    ),
+            )
+    }
+
+    /// Drops the vault from the registry and refunds the deposit if it failed.
+    #[private]
+    pub fn after_create_vault(
+        &mut self,
+        vault_id: AccountId,
+        creator_id: AccountId,
+        amount: U128,
+    ) -> bool {
+        let promise_success = is_promise_success();
+        if promise_success {
+            log!("Vault {} created", vault_id);
+        } else {
+            self.vaults.remove(&vault_id);
+            Promise::new(creator_id).transfer(NearToken::from_yoctonear(amount.0));
+            log!("Vault {} creation failed and refunded", vault_id);
+        }
+        promise_success
+    }
+
+    /// Records the new code of the vault if its upgrade succeeded.
+    #[private]
+    pub fn after_upgrade_vault(
+        &mut self,
+        vault_id: AccountId,
+        code_hash: Base58CryptoHash,
+    ) -> bool {
+        let promise_success = is_promise_success();
+        if promise_success {
+            if let Some(vault) = self.vaults.get_mut(&vault_id) {
+                vault.code_hash = code_hash;
+            }
+            log!("Vault {} upgraded", vault_id);
+        } else {
+            log!("Vault {} upgrade failed", vault_id);
+        }
+        promise_success
+    }
+}
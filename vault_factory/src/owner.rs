@@ -0,0 +1,94 @@
+//! Implement all the relevant logic for owner of this contract.
+use crate::*;
+use near_sdk::{assert_one_yocto, StorageUsage};
+
+impl Contract {
+    /// Check how much storage taken costs and refund the left over back.
+    pub(crate) fn internal_check_storage(&self, prev_storage: StorageUsage) {
+        let storage = env::storage_usage().saturating_sub(prev_storage) as u128;
+        let storage_cost = env::storage_byte_cost().checked_mul(storage).unwrap();
+
+        let msg = format!(
+            "ERR_STORAGE_DEPOSIT need {}, attatched {}",
+            storage_cost,
+            env::attached_deposit()
+        );
+        let refund = env::attached_deposit()
+            .checked_sub(storage_cost)
+            .expect(&msg);
+        if refund > NearToken::from_yoctonear(0) {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "ERR_NOT_ALLOWED"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Change owner. Only can be called by owner.
+    #[payable]
+    pub fn set_owner(&mut self, owner_id: AccountId) {
+        self.assert_owner();
+        assert_one_yocto();
+        self.owner_id = owner_id;
+    }
+
+    /// Store the session_vault code passed as raw input, used by new vaults and upgrades.
+    /// Attached deposit covers the storage of the code, the rest is refunded.
+    /// Only can be called by owner.
+    #[payable]
+    pub fn store_vault_code(&mut self) -> Base58CryptoHash {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        let code = env::input().expect("ERR_NO_CODE");
+        let code_hash: Base58CryptoHash = env::sha256_array(&code).into();
+        env::storage_write(VAULT_CODE_KEY, &code);
+        self.code_hash = Some(code_hash);
+        self.internal_check_storage(prev_storage);
+        log!("Stored vault code {}", String::from(&code_hash));
+        code_hash
+    }
+
+    /// Upgrade the given vaults, or all of them, to the stored code.
+    /// Vaults already on it are skipped, at most MAX_UPGRADE_LIMIT are upgraded per call.
+    /// Returns how many upgrades were started. Only can be called by owner.
+    #[payable]
+    pub fn upgrade_vaults(&mut self, vault_ids: Option<Vec<AccountId>>) -> u32 {
+        self.assert_owner();
+        assert_one_yocto();
+        let code_hash = self.code_hash.expect("ERR_NO_VAULT_CODE");
+        let code = env::storage_read(VAULT_CODE_KEY).expect("ERR_NO_VAULT_CODE");
+        let vault_ids = vault_ids.unwrap_or_else(|| self.vaults.keys().cloned().collect());
+        let mut upgraded = 0;
+        for vault_id in vault_ids {
+            let vault = self.vaults.get(&vault_id).expect("ERR_VAULT_NOT_EXIST");
+            if vault.code_hash == code_hash {
+                continue;
+            }
+            assert!(upgraded < MAX_UPGRADE_LIMIT as u32, "ERR_TOO_MANY_UPGRADES");
+            // the promise is scheduled when dropped
+            let _ = Promise::new(vault_id.clone())
+                .function_call(
+                    "upgrade".to_string(),
+                    code.clone(),
+                    ONE_YOCTO,
+                    GAS_FOR_VAULT_UPGRADE,
+                )
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_attached_deposit(NO_DEPOSIT)
+                        .with_static_gas(GAS_FOR_AFTER_UPGRADE_VAULT)
+                        .after_upgrade_vault(vault_id, code_hash),
+                );
+            upgraded += 1;
+        }
+        upgraded
+    }
+}
@@ -0,0 +1,21 @@
+use near_sdk::{json_types::U64, Gas, NearToken};
+
+pub type TimestampSec = U64;
+
+/// Storage key of the session_vault code, apart from the contract state.
+pub const VAULT_CODE_KEY: &[u8] = b"VAULT_CODE";
+
+/// Bytes a new vault needs besides its code: account and initial state.
+pub const VAULT_INIT_STORAGE: u128 = 10_000;
+
+pub const GAS_FOR_VAULT_INIT: Gas = Gas::from_gas(20_000_000_000_000);
+pub const GAS_FOR_AFTER_CREATE_VAULT: Gas = Gas::from_gas(10_000_000_000_000);
+/// Deploys the code to the vault and migrates it.
+pub const GAS_FOR_VAULT_UPGRADE: Gas = Gas::from_gas(70_000_000_000_000);
+pub const GAS_FOR_AFTER_UPGRADE_VAULT: Gas = Gas::from_gas(10_000_000_000_000);
+
+/// Each upgrade carries the whole code and its callback.
+pub const MAX_UPGRADE_LIMIT: usize = 3;
+
+pub const NO_DEPOSIT: NearToken = NearToken::from_yoctonear(0);
+pub const ONE_YOCTO: NearToken = NearToken::from_yoctonear(1);
@@ -0,0 +1,35 @@
+use crate::*;
+
+#[near]
+impl Contract {
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Hash of the code new vaults get, and upgrades roll out.
+    pub fn get_vault_code_hash(&self) -> Option<Base58CryptoHash> {
+        self.code_hash
+    }
+
+    pub fn get_vault(&self, vault_id: AccountId) -> Option<VaultInfo> {
+        self.vaults.get(&vault_id).cloned()
+    }
+
+    /// Deployed vaults, only the ones with `label` if given.
+    pub fn list_vaults(
+        &self,
+        label: Option<String>,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<VaultInfo> {
+        let from_index = from_index.unwrap_or(U64(0)).0 as usize;
+        let limit = limit.unwrap_or(U64(self.vaults.len() as u64)).0 as usize;
+        self.vaults
+            .values()
+            .filter(|vault| label.as_ref().is_none_or(|label| &vault.label == label))
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
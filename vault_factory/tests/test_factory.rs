@@ -0,0 +1,136 @@
+use near_sdk::json_types::{Base58CryptoHash, U64};
+use near_sdk::serde::Deserialize;
+use near_sdk::{AccountId, NearToken};
+
+#[derive(Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultInfo {
+    pub vault_id: AccountId,
+    pub label: String,
+    pub token_id: AccountId,
+    pub code_hash: Base58CryptoHash,
+    pub created_at: U64,
+}
+
+fn read_wasm(path: &str) -> Vec<u8> {
+    std::fs::read(path)
+        .unwrap_or_else(|err| panic!("Could not read WASM file from {}\nErr: {err}", path))
+}
+
+#[tokio::test]
+async fn sim_create_and_upgrade_vault() {
+    let worker = near_workspaces::sandbox().await.unwrap();
+    let root_account = worker.root_account().unwrap();
+    let owner = root_account
+        .create_subaccount("owner")
+        .initial_balance(NearToken::from_near(50))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let factory = root_account
+        .create_subaccount("factory")
+        .initial_balance(NearToken::from_near(50))
+        .transact()
+        .await
+        .unwrap()
+        .result;
+    let factory = factory
+        .deploy(&read_wasm("../res/vault_factory.wasm"))
+        .await
+        .unwrap()
+        .result;
+    let res = factory
+        .call("new")
+        .args_json((owner.id(),))
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let res = owner
+        .call(factory.id(), "store_vault_code")
+        .args(read_wasm("../res/session_vault.wasm"))
+        .deposit(NearToken::from_near(10))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    let code_hash = res.json::<Base58CryptoHash>().unwrap();
+
+    let res = owner
+        .call(factory.id(), "create_vault")
+        .args_json(("strategic", "strategic", owner.id(), "test_token.test.near"))
+        .deposit(NearToken::from_near(10))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+
+    let vaults = factory
+        .view("list_vaults")
+        .args_json((Some("strategic"), Option::<U64>::None, Option::<U64>::None))
+        .await
+        .unwrap()
+        .json::<Vec<VaultInfo>>()
+        .unwrap();
+    assert_eq!(vaults.len(), 1);
+    let vault_id: AccountId = format!("strategic.{}", factory.id()).parse().unwrap();
+    assert_eq!(vaults[0].vault_id, vault_id);
+    assert_eq!(vaults[0].code_hash, code_hash);
+    let metadata = worker
+        .view(&vault_id, "contract_metadata")
+        .await
+        .unwrap()
+        .json::<near_sdk::serde_json::Value>()
+        .unwrap();
+    assert_eq!(metadata["owner_id"], owner.id().to_string());
+    assert_eq!(metadata["factory_id"], factory.id().to_string());
+
+    // same name again
+    let res = owner
+        .call(factory.id(), "create_vault")
+        .args_json(("strategic", "private", owner.id(), "test_token.test.near"))
+        .deposit(NearToken::from_near(10))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("ERR_VAULT_ALREADY_EXIST"),
+        "got {:?}",
+        failure
+    );
+
+    // already on the stored code
+    let res = owner
+        .call(factory.id(), "upgrade_vaults")
+        .args_json((Option::<Vec<AccountId>>::None,))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_success(), "Res is {:?}", res);
+    assert_eq!(res.json::<u32>().unwrap(), 0);
+
+    // the vault takes an upgrade only with one yocto attached
+    let res = owner
+        .call(&vault_id, "upgrade")
+        .args(read_wasm("../res/session_vault.wasm"))
+        .max_gas()
+        .transact()
+        .await
+        .unwrap();
+    assert!(res.is_failure());
+    let failure = format!("{:?}", res.failures().first());
+    assert!(
+        failure.contains("Requires attached deposit of exactly 1 yoctoNEAR"),
+        "got {:?}",
+        failure
+    );
+}